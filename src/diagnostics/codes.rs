@@ -0,0 +1,338 @@
+/// A stable identifier for a class of errors, with a longer description
+/// that `cog explain <code>` prints.
+#[derive(Debug, PartialEq)]
+pub struct ErrorCode {
+    pub(crate) code: &'static str,
+    pub(crate) title: &'static str,
+    pub(crate) explanation: &'static str,
+}
+
+// lexer errors
+
+pub const UNEXPECTED_CHARACTER: ErrorCode = ErrorCode {
+    code: "E0001",
+    title: "unexpected character",
+    explanation: "\
+The lexer found a character that does not start any token in Cog.
+
+Identifiers must start with a letter, numbers with a digit, and strings with a
+quote. Any other character has to be one of the supported operators or
+punctuation: + - * / % = ! ~ && || < > ( ) { } [ ] , : ; .",
+};
+
+pub const INVALID_STRING: ErrorCode = ErrorCode {
+    code: "E0002",
+    title: "invalid string literal",
+    explanation: "\
+A string literal contains an escape sequence that Cog does not understand.
+The supported escapes are:
+
+    \\n  \\t  \\r  \\0  \\\\  \\\"  \\'  \\$  \\u{1F600}
+
+Unicode escapes take between 1 and 6 hexadecimal digits naming a valid code
+point. To write backslashes without escaping them, use a raw string:
+
+    let path = r\"C:\\files\\new\";",
+};
+
+pub const INVALID_NUMBER: ErrorCode = ErrorCode {
+    code: "E0003",
+    title: "invalid numeric literal",
+    explanation: "\
+A numeric literal is malformed. Cog accepts the following forms:
+
+    42  1_000_000         decimal integers, `_` separates digit groups
+    3.14  .5              decimals, with or without a leading digit
+    6.02e23  1E-3         scientific notation
+    0xFF  0b1010  0o17    hexadecimal, binary and octal integers
+
+Digit separators must sit between two digits, prefixed literals only accept
+the digits of their base, an exponent needs at least one digit, and a number
+cannot be directly followed by letters as in `12abc`.",
+};
+
+pub const UNTERMINATED_STRING: ErrorCode = ErrorCode {
+    code: "E0004",
+    title: "unterminated string literal",
+    explanation: "\
+A string literal was opened but the file ended before its closing quote:
+
+    let greeting = \"hello;
+
+Strings may span several lines, so the missing quote can be far from where
+the error is reported. Raw strings opened with `r#\"` must be closed with `\"#`
+using the same number of `#`.",
+};
+
+pub const UNTERMINATED_COMMENT: ErrorCode = ErrorCode {
+    code: "E0005",
+    title: "unterminated block comment",
+    explanation: "\
+A `/*` block comment was never closed. Block comments nest, so every `/*`
+inside a comment needs its own `*/`:
+
+    /* outer /* inner */ still a comment */
+
+Line comments start with `//` and end at the end of the line.",
+};
+
+// parser errors
+
+pub const UNEXPECTED_TOKEN: ErrorCode = ErrorCode {
+    code: "E0100",
+    title: "unexpected token",
+    explanation: "\
+The parser found a token that cannot appear at this point of the program, for
+example a missing closing brace or a missing `;` after a variable declaration:
+
+    let x = 5        // error: expected `;`
+    print(x)
+
+The message says which token was expected. Every syntax error in a file is
+reported, so fixing the first one may also fix some of the following ones.",
+};
+
+pub const EXPECTED_IDENTIFIER: ErrorCode = ErrorCode {
+    code: "E0101",
+    title: "expected identifier",
+    explanation: "\
+A name was expected, for example after `let`, `const`, `fn`, `for`, after a `.`
+in a member expression, as a function parameter or as an object key.
+
+    let 5 = x;       // error
+    fn add(1, b) {}  // error
+
+Names start with a letter.",
+};
+
+pub const MISSING_CONSTANT_VALUE: ErrorCode = ErrorCode {
+    code: "E0102",
+    title: "constant without a value",
+    explanation: "\
+Constants can never be assigned after they are declared, so they must be given
+a value in their declaration.
+
+    const limit;         // error
+    const limit = 10;    // ok",
+};
+
+pub const RETURN_OUTSIDE_FUNCTION: ErrorCode = ErrorCode {
+    code: "E0103",
+    title: "return outside of a function",
+    explanation: "\
+`return` exits the function it appears in, so it can only be used inside a
+function body:
+
+    fn sign(n) {
+        if n < 0 { return -1; }
+        1
+    }
+
+At the top level of a file there is no function to return from.",
+};
+
+pub const LOOP_CONTROL_OUTSIDE_LOOP: ErrorCode = ErrorCode {
+    code: "E0104",
+    title: "break or continue outside of a loop",
+    explanation: "\
+`break` and `continue` only make sense inside the body of a `for` or `while`
+loop. A function body starts a new context, so a loop around a function
+declaration cannot be exited from inside the function:
+
+    for i in 0..10 {
+        fn stop() { break; }    // error
+        if i == 5 { break; }    // ok
+    }",
+};
+
+pub const UNDEFINED_LABEL: ErrorCode = ErrorCode {
+    code: "E0105",
+    title: "undefined loop label",
+    explanation: "\
+`break` and `continue` can name the loop they apply to with a label, which has
+to belong to one of the loops enclosing the statement:
+
+    'outer: for i in 0..10 {
+        for j in 0..10 {
+            if i * j > 20 { break 'outer; }
+        }
+    }
+
+Check the spelling of the label and that the labeled loop contains the
+statement.",
+};
+
+pub const INVALID_PARAMETER: ErrorCode = ErrorCode {
+    code: "E0106",
+    title: "invalid parameter list",
+    explanation: "\
+Parameters with a default value are optional, so they can only be followed by
+other optional parameters. A rest parameter collects every remaining argument
+into an array, so it must be the last parameter and cannot have a default:
+
+    fn greet(name, greeting = \"hello\") {}    // ok
+    fn sum(first, ...others) {}               // ok
+    fn broken(a = 1, b) {}                    // error
+    fn broken(...others, last) {}             // error",
+};
+
+pub const INVALID_ASSIGNMENT_TARGET: ErrorCode = ErrorCode {
+    code: "E0107",
+    title: "invalid assignment target",
+    explanation: "\
+The left side of `=` must be a variable (`x = 1`), an object property
+(`obj.x = 1`, `obj[\"x\"] = 1`) or an array element (`items[0] = 1`). Other
+expressions such as `1 = 2` or `f() = 3` cannot be assigned to.",
+};
+
+// runtime errors
+
+pub const UNDEFINED_VARIABLE: ErrorCode = ErrorCode {
+    code: "E0200",
+    title: "undefined variable",
+    explanation: "\
+A variable was used before it was declared, or it is not visible from the
+current scope.
+
+    print(total)     // error: `total` is not declared
+    let total = 10;
+
+Declare variables with `let` or `const` before using them. Variables declared
+inside a block, loop or function are only visible inside it.",
+};
+
+pub const VARIABLE_ALREADY_DECLARED: ErrorCode = ErrorCode {
+    code: "E0201",
+    title: "variable already declared",
+    explanation: "\
+A variable with the same name was already declared in the same scope.
+
+    let x = 1;
+    let x = 2;       // error
+
+Assign to the existing variable instead (`x = 2`), or pick another name.
+Declaring a variable with the same name inside a nested block is allowed.",
+};
+
+pub const CONSTANT_REASSIGNMENT: ErrorCode = ErrorCode {
+    code: "E0202",
+    title: "assignment to a constant",
+    explanation: "\
+A variable declared with `const` cannot be assigned a new value.
+
+    const limit = 10;
+    limit = 20       // error
+
+Declare it with `let` if it needs to change. Built-in names such as `true`,
+`false`, `null` and `print` are constants as well.",
+};
+
+pub const TYPE_ERROR: ErrorCode = ErrorCode {
+    code: "E0203",
+    title: "type error",
+    explanation: "\
+An operation was applied to values of types it does not support, for example
+ordering a number against a string (`1 < \"a\"`), reading a property of a
+number, indexing an array with a string, or iterating over something that is
+not a range, an array or an object.",
+};
+
+pub const ARITY_MISMATCH: ErrorCode = ErrorCode {
+    code: "E0204",
+    title: "wrong number of arguments",
+    explanation: "\
+A function was called with a different number of arguments than it accepts.
+
+    fn add(a, b) { a + b }
+    add(1)           // error: `add` expects 2 arguments
+
+Give a parameter a default value to make it optional (`fn add(a, b = 0)`), or
+collect any number of extra arguments into an array with a rest parameter
+(`fn sum(...numbers)`).",
+};
+
+pub const DIVISION_BY_ZERO: ErrorCode = ErrorCode {
+    code: "E0205",
+    title: "division by zero",
+    explanation: "\
+A number was divided by zero, either with `/` or when taking the remainder
+with `%`. Check the divisor before dividing:
+
+    if divisor != 0 { total / divisor }",
+};
+
+pub const NOT_CALLABLE: ErrorCode = ErrorCode {
+    code: "E0206",
+    title: "value is not callable",
+    explanation: "\
+Only functions can be called. The value before the parentheses evaluated to
+something else, such as a number, string or object.",
+};
+
+pub const INDEX_OUT_OF_BOUNDS: ErrorCode = ErrorCode {
+    code: "E0208",
+    title: "index out of bounds",
+    explanation: "\
+An array was read or written at an index it does not have. Indices start at 0
+and negative indices count from the end, so for an array of length 3 the valid
+indices are -3 to 2:
+
+    let items = [10, 20, 30];
+    items[2]         // 30
+    items[-1]        // 30
+    items[3]         // error
+
+Use `items.length` to check the size of an array before indexing it.",
+};
+
+pub const CALL_DEPTH_EXCEEDED: ErrorCode = ErrorCode {
+    code: "E0209",
+    title: "maximum call depth exceeded",
+    explanation: "\
+Too many function calls were in progress at the same time, at most 1000 calls
+can be nested. This almost always means a recursive function never reaches its
+base case:
+
+    fn countdown(n) {
+        countdown(n - 1);    // error: nothing stops the recursion
+    }
+
+    fn countdown(n) {
+        if n == 0 { return; }
+        countdown(n - 1);    // ok
+    }
+
+Deep recursion that does terminate can usually be rewritten as a loop.",
+};
+
+pub const ERROR_CODES: &[&ErrorCode] = &[
+    &UNEXPECTED_CHARACTER,
+    &INVALID_STRING,
+    &INVALID_NUMBER,
+    &UNTERMINATED_STRING,
+    &UNTERMINATED_COMMENT,
+    &UNEXPECTED_TOKEN,
+    &EXPECTED_IDENTIFIER,
+    &MISSING_CONSTANT_VALUE,
+    &RETURN_OUTSIDE_FUNCTION,
+    &LOOP_CONTROL_OUTSIDE_LOOP,
+    &UNDEFINED_LABEL,
+    &INVALID_PARAMETER,
+    &INVALID_ASSIGNMENT_TARGET,
+    &UNDEFINED_VARIABLE,
+    &VARIABLE_ALREADY_DECLARED,
+    &CONSTANT_REASSIGNMENT,
+    &TYPE_ERROR,
+    &ARITY_MISMATCH,
+    &DIVISION_BY_ZERO,
+    &NOT_CALLABLE,
+    &INDEX_OUT_OF_BOUNDS,
+    &CALL_DEPTH_EXCEEDED,
+];
+
+pub fn find_error_code(code: &str) -> Option<&'static ErrorCode> {
+    ERROR_CODES
+        .iter()
+        .find(|error_code| error_code.code.eq_ignore_ascii_case(code))
+        .copied()
+}
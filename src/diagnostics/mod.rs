@@ -0,0 +1,3 @@
+pub(crate) mod codes;
+pub(crate) mod diagnostic;
+pub(crate) mod render;
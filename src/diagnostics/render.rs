@@ -0,0 +1,232 @@
+use std::fmt::Write;
+
+use super::diagnostic::{Diagnostic, Severity};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+
+/// Renders diagnostics for a single source file, in the form
+///
+/// ```text
+/// error[E0200]: Variable y not found
+///  --> main.cog:2:11
+///   |
+/// 2 | print(x + y);
+///   |           ^ undefined variable
+///   |
+///   = help: declare the variable with `let` or `const` before using it
+/// ```
+pub struct Renderer<'a> {
+    file_name: &'a str,
+    source: &'a str,
+    color: bool,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(file_name: &'a str, source: &'a str, color: bool) -> Self {
+        Self {
+            file_name,
+            source,
+            color,
+        }
+    }
+
+    fn paint(&self, style: &str, text: &str) -> String {
+        if self.color {
+            format!("{}{}{}", style, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let mut output = String::new();
+
+        let (severity, severity_style) = match diagnostic.severity {
+            Severity::Error => ("error", RED),
+            Severity::Warning => ("warning", YELLOW),
+        };
+
+        let header = match diagnostic.code {
+            Some(code) => format!("{}[{}]", severity, code.code),
+            None => severity.to_string(),
+        };
+
+        writeln!(
+            output,
+            "{}{}",
+            self.paint(severity_style, &header),
+            self.paint(BOLD, &format!(": {}", diagnostic.message))
+        )
+        .unwrap();
+
+        let Some(span) = diagnostic.span else {
+            writeln!(output, "{} {}", self.paint(BLUE, "-->"), self.file_name).unwrap();
+            self.render_footer(&mut output, diagnostic, 1);
+            return output;
+        };
+
+        let line_number = span.start.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+        let source_line = self.source.lines().nth(span.start.line - 1).unwrap_or("");
+
+        // underline up to the end of the span, or the end of the line for multi-line spans
+        let line_length = source_line.chars().count() + 1;
+        let underline_end = if span.end.line == span.start.line {
+            span.end.column
+        } else {
+            line_length
+        };
+        let underline_length = underline_end.saturating_sub(span.start.column).max(1);
+
+        writeln!(
+            output,
+            "{}{} {}:{}:{}",
+            gutter,
+            self.paint(BLUE, "-->"),
+            self.file_name,
+            span.start.line,
+            span.start.column
+        )
+        .unwrap();
+        writeln!(output, "{} {}", gutter, self.paint(BLUE, "|")).unwrap();
+        writeln!(
+            output,
+            "{} {}",
+            self.paint(BLUE, &format!("{} |", line_number)),
+            source_line
+        )
+        .unwrap();
+
+        let mut underline = format!(
+            "{}{}",
+            " ".repeat(span.start.column - 1),
+            "^".repeat(underline_length)
+        );
+
+        if let Some(label) = &diagnostic.label {
+            underline.push(' ');
+            underline.push_str(label);
+        }
+
+        writeln!(
+            output,
+            "{} {} {}",
+            gutter,
+            self.paint(BLUE, "|"),
+            self.paint(severity_style, &underline)
+        )
+        .unwrap();
+
+        self.render_footer(&mut output, diagnostic, line_number.len());
+
+        output
+    }
+
+    fn render_footer(&self, output: &mut String, diagnostic: &Diagnostic, gutter_width: usize) {
+        if diagnostic.notes.is_empty() && diagnostic.help.is_none() {
+            return;
+        }
+
+        let gutter = " ".repeat(gutter_width);
+
+        writeln!(output, "{} {}", gutter, self.paint(BLUE, "|")).unwrap();
+
+        for note in &diagnostic.notes {
+            writeln!(
+                output,
+                "{} {} {}",
+                gutter,
+                self.paint(BLUE, "="),
+                self.paint(BOLD, "note:") + " " + note
+            )
+            .unwrap();
+        }
+
+        if let Some(help) = &diagnostic.help {
+            writeln!(
+                output,
+                "{} {} {}",
+                gutter,
+                self.paint(BLUE, "="),
+                self.paint(BOLD, "help:") + " " + help
+            )
+            .unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::codes::UNDEFINED_VARIABLE;
+    use crate::frontend::span::{Position, Span};
+
+    const SOURCE: &str = "let x = 1;\nprint(x + y);\n";
+
+    fn span(start: (usize, usize, usize), end: (usize, usize, usize)) -> Option<Span> {
+        Some(Span::new(
+            Position::new(start.0, start.1, start.2),
+            Position::new(end.0, end.1, end.2),
+        ))
+    }
+
+    #[test]
+    fn renders_the_source_line_under_the_header() {
+        let diagnostic = Diagnostic::error("Variable y not found")
+            .with_code(&UNDEFINED_VARIABLE)
+            .with_span(span((21, 2, 11), (22, 2, 12)))
+            .with_label("undefined variable")
+            .with_note("variables are visible from where they are declared")
+            .with_help("declare the variable with `let` or `const` before using it");
+
+        assert_eq!(
+            Renderer::new("main.cog", SOURCE, false).render(&diagnostic),
+            "error[E0200]: Variable y not found\n \
+             --> main.cog:2:11\n  \
+             |\n\
+             2 | print(x + y);\n  \
+             |           ^ undefined variable\n  \
+             |\n  \
+             = note: variables are visible from where they are declared\n  \
+             = help: declare the variable with `let` or `const` before using it\n"
+        );
+    }
+
+    #[test]
+    fn underlines_multi_line_spans_to_the_end_of_the_first_line() {
+        let diagnostic = Diagnostic::warning("unused value").with_span(span((4, 1, 5), (17, 2, 7)));
+
+        assert_eq!(
+            Renderer::new("main.cog", SOURCE, false).render(&diagnostic),
+            "warning: unused value\n \
+             --> main.cog:1:5\n  \
+             |\n\
+             1 | let x = 1;\n  \
+             |     ^^^^^^\n"
+        );
+    }
+
+    #[test]
+    fn renders_diagnostics_without_a_span_against_the_file() {
+        let diagnostic = Diagnostic::error("Unexpected end of input");
+
+        assert_eq!(
+            Renderer::new("main.cog", SOURCE, false).render(&diagnostic),
+            "error: Unexpected end of input\n--> main.cog\n"
+        );
+    }
+
+    #[test]
+    fn colors_only_when_asked_to() {
+        let diagnostic = Diagnostic::error("Unexpected end of input");
+        let rendered = Renderer::new("main.cog", SOURCE, true).render(&diagnostic);
+
+        assert!(
+            rendered.starts_with("\x1b[1;31merror\x1b[0m\x1b[1m: Unexpected end of input\x1b[0m\n")
+        );
+    }
+}
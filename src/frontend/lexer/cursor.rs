@@ -0,0 +1,70 @@
+use crate::frontend::span::Position;
+
+/// Walks the source one character at a time while keeping track of the current
+/// position. Every operation is O(1), so lexing a file is linear in its size.
+pub struct Cursor<'a> {
+    source: &'a str,
+    position: Position,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            position: Position::default(),
+        }
+    }
+
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// The source that has not been consumed yet.
+    pub fn rest(&self) -> &'a str {
+        &self.source[self.position.offset..]
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    /// Looks `n` characters ahead without consuming anything, `peek_nth(0)` is `peek()`.
+    pub fn peek_nth(&self, n: usize) -> Option<char> {
+        self.rest().chars().nth(n)
+    }
+
+    pub fn advance(&mut self) -> Option<char> {
+        let char = self.peek()?;
+
+        self.position.offset += char.len_utf8();
+        self.position.column += 1;
+
+        if char == '\n' {
+            self.position.line += 1;
+            self.position.column = 1;
+        }
+
+        Some(char)
+    }
+
+    /// Consumes the next character if it is `expected`.
+    pub fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.advance();
+            return true;
+        }
+
+        false
+    }
+
+    /// Consumes characters while `predicate` holds and returns the consumed slice.
+    pub fn eat_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let start = self.position.offset;
+
+        while matches!(self.peek(), Some(char) if predicate(char)) {
+            self.advance();
+        }
+
+        &self.source[start..self.position.offset]
+    }
+}
@@ -1,3 +1,45 @@
-// pub fn diplay_lexing_error(line_number: usize, start: usize, end: usize, msg: &str) {
-//     println!("{:?}", line_number);
-// }
+use crate::{
+    diagnostics::{codes, diagnostic::Diagnostic},
+    frontend::span::Span,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LexErrorKind {
+    UnexpectedCharacter,
+    InvalidString,
+    InvalidNumber,
+    UnterminatedString,
+    UnterminatedComment,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexError {
+    pub(crate) kind: LexErrorKind,
+    pub(crate) message: String,
+    pub(crate) span: Span,
+}
+
+impl LexError {
+    pub fn new(kind: LexErrorKind, message: impl Into<String>, span: Span) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            span,
+        }
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let code = match self.kind {
+            LexErrorKind::UnexpectedCharacter => &codes::UNEXPECTED_CHARACTER,
+            LexErrorKind::InvalidString => &codes::INVALID_STRING,
+            LexErrorKind::InvalidNumber => &codes::INVALID_NUMBER,
+            LexErrorKind::UnterminatedString => &codes::UNTERMINATED_STRING,
+            LexErrorKind::UnterminatedComment => &codes::UNTERMINATED_COMMENT,
+        };
+
+        Diagnostic::error(self.message.clone())
+            .with_code(code)
+            .with_span(Some(self.span))
+            .with_label(code.title)
+    }
+}
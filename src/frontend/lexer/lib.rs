@@ -1,6 +1,10 @@
-use std::io::Write;
-
-use crate::{frontend::parser::ast::ASTExpression, helpers::is_string::LiteralHelpers};
+use crate::frontend::{
+    lexer::{
+        cursor::Cursor,
+        error::{LexError, LexErrorKind},
+    },
+    span::{Position, Span},
+};
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 
@@ -17,6 +21,9 @@ pub enum Type {
     If,
     Else,
     While,
+    Return,
+    Break,
+    Continue,
 
     // operators
     Operator,          // +, -, *, /
@@ -27,7 +34,11 @@ pub enum Type {
     GreaterEqual,      // >=
     LessEqual,         // <=
     Not,               // !
+    Tilde,             // ~
     NotEqual,          // !=
+    DoubleEquals,      // ==
+    And,               // &&
+    Or,                // ||
     OpenParen,         // (
     CloseParen,        // )
     Comma,             // ,
@@ -38,11 +49,18 @@ pub enum Type {
     CloseBracket,      // ]
     Semi,              // ;
     Dot,               // .
+    Ellipsis,          // ...
     Equals,            // =
+    Arrow,             // =>
 
     // values
     Number,
     String,
+    Label,          // 'outer
+    TemplateHead,   // "text ${
+    TemplateMiddle, // } text ${
+    TemplateTail,   // } text"
+    DocComment,     // /// ...
     EOF,
 }
 
@@ -50,336 +68,979 @@ pub enum Type {
 pub enum Value {
     String(String),
     Number(f64),
-    Object(Object),
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub struct Object {
-    pub(crate) properties: Vec<Property>,
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub struct Property {
-    pub(crate) key: String,
-    pub(crate) value: Option<ASTExpression>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Token {
     pub(crate) r#type: Type,
     pub(crate) value: Value,
+    pub(crate) span: Span,
 }
 
-pub fn tokenize(input: &String) -> Result<Vec<Token>, String> {
-    const NEW_LINE_CHARACTER: char = 0xA as char;
-    let operators = vec![
-        '+',
-        '-',
-        '*',
-        '/',
-        '%',
-        ':',
-        '=',
-        '(',
-        ')',
-        '{',
-        '}',
-        ';',
-        ',',
-        '[',
-        ']',
-        '.',
-        '>',
-        '<',
-        '!',
-        NEW_LINE_CHARACTER,
-    ];
+/// A string literal whose `${ ... }` interpolation is being lexed. `depth` counts the
+/// braces opened inside the interpolation, the `}` seen at depth 0 resumes the string.
+struct OpenInterpolation {
+    quote: char,
+    opening: Span,
+    depth: usize,
+}
+
+/// Interpolated strings are split into several tokens: `"a ${x} b ${y} c"` becomes
+/// `TemplateHead("a ")`, `x`, `TemplateMiddle(" b ")`, `y`, `TemplateTail(" c")`.
+pub fn tokenize(input: &str) -> Result<Vec<Token>, LexError> {
+    let mut cursor = Cursor::new(input);
     let mut tokens: Vec<Token> = vec![];
-    let mut cursor: usize = 0;
-
-    while cursor < input.len() {
-        let char = input.chars().nth(cursor).expect("internal error");
-
-        match char {
-            ' ' => {}
-            NEW_LINE_CHARACTER => {}
-            '=' => tokens.push(Token {
-                r#type: Type::Equals,
-                value: Value::String(String::from("=")),
-            }),
-            '+' => tokens.push(Token {
-                r#type: Type::Operator,
-                value: Value::String(String::from("+")),
-            }),
-            ',' => tokens.push(Token {
-                r#type: Type::Comma,
-                value: Value::String(String::from(",")),
-            }),
-            '!' => {
-                let next_char = input.chars().nth(cursor + 1);
-
-                if next_char == Some('=') {
-                    tokens.push(Token {
-                        r#type: Type::NotEqual,
-                        value: Value::String(String::from("!=")),
-                    });
-                    cursor += 1;
+    let mut interpolations: Vec<OpenInterpolation> = vec![];
+
+    while let Some(char) = cursor.peek() {
+        let start = cursor.position();
+
+        if char.is_whitespace() {
+            cursor.advance();
+            continue;
+        }
+
+        let (r#type, value) = match char {
+            '=' => {
+                cursor.advance();
+
+                if cursor.eat('=') {
+                    symbol(Type::DoubleEquals, "==")
+                } else if cursor.eat('>') {
+                    symbol(Type::Arrow, "=>")
                 } else {
-                    tokens.push(Token {
-                        r#type: Type::Not,
-                        value: Value::String(String::from("!")),
-                    })
+                    symbol(Type::Equals, "=")
                 }
             }
-            ':' => tokens.push(Token {
-                r#type: Type::Colon,
-                value: Value::String(String::from(":")),
-            }),
-            '>' | '<' => {
-                let next_char = input.chars().nth(cursor + 1);
-
-                if next_char == Some('=') {
-                    match char {
-                        '>' => tokens.push(Token {
-                            r#type: Type::GreaterEqual,
-                            value: Value::String(String::from(">=")),
-                        }),
-                        '<' => tokens.push(Token {
-                            r#type: Type::LessEqual,
-                            value: Value::String(String::from("<=")),
-                        }),
-                        _ => panic!("internal error"),
-                    }
+            '!' => {
+                cursor.advance();
 
-                    cursor += 1;
-                } else {
-                    match char {
-                        '>' => tokens.push(Token {
-                            r#type: Type::GreaterThan,
-                            value: Value::String(String::from(">")),
-                        }),
-                        '<' => tokens.push(Token {
-                            r#type: Type::LessThan,
-                            value: Value::String(String::from("<")),
-                        }),
-                        _ => panic!("internal error"),
-                    }
+                match cursor.eat('=') {
+                    true => symbol(Type::NotEqual, "!="),
+                    false => symbol(Type::Not, "!"),
                 }
             }
-            '{' => tokens.push(Token {
-                r#type: Type::OpenBrace,
-                value: Value::String(String::from("{")),
-            }),
-            '}' => tokens.push(Token {
-                r#type: Type::CloseBrace,
-                value: Value::String(String::from("}")),
-            }),
-            '.' => {
-                let next_char = input.chars().nth(cursor + 1);
+            '~' => {
+                cursor.advance();
+                symbol(Type::Tilde, "~")
+            }
+            '&' if cursor.peek_nth(1) == Some('&') => {
+                cursor.advance();
+                cursor.advance();
+                symbol(Type::And, "&&")
+            }
+            '|' if cursor.peek_nth(1) == Some('|') => {
+                cursor.advance();
+                cursor.advance();
+                symbol(Type::Or, "||")
+            }
+            '>' => {
+                cursor.advance();
 
-                if next_char == Some('.') {
-                    let inclusive = input.chars().nth(cursor + 2);
+                match cursor.eat('=') {
+                    true => symbol(Type::GreaterEqual, ">="),
+                    false => symbol(Type::GreaterThan, ">"),
+                }
+            }
+            '<' => {
+                cursor.advance();
 
-                    if inclusive == Some('=') {
-                        tokens.push(Token {
-                            r#type: Type::InclusiveInterval,
-                            value: Value::String(String::from("..=")),
-                        });
-                        cursor += 2;
+                match cursor.eat('=') {
+                    true => symbol(Type::LessEqual, "<="),
+                    false => symbol(Type::LessThan, "<"),
+                }
+            }
+            '.' if cursor.peek_nth(1).is_some_and(|char| char.is_ascii_digit()) => {
+                (Type::Number, Value::Number(lex_number(&mut cursor)?))
+            }
+            '.' => {
+                cursor.advance();
+
+                if cursor.eat('.') {
+                    if cursor.eat('.') {
+                        symbol(Type::Ellipsis, "...")
+                    } else if cursor.eat('=') {
+                        symbol(Type::InclusiveInterval, "..=")
                     } else {
-                        tokens.push(Token {
-                            r#type: Type::Interval,
-                            value: Value::String(String::from("..")),
-                        });
-                        cursor += 1;
+                        symbol(Type::Interval, "..")
                     }
                 } else {
-                    tokens.push(Token {
-                        r#type: Type::Dot,
-                        value: Value::String(String::from(".")),
-                    });
+                    symbol(Type::Dot, ".")
                 }
             }
-            '[' => tokens.push(Token {
-                r#type: Type::OpenBracket,
-                value: Value::String(String::from("[")),
-            }),
-            ']' => tokens.push(Token {
-                r#type: Type::CloseBracket,
-                value: Value::String(String::from("]")),
-            }),
-            '%' => tokens.push(Token {
-                r#type: Type::Operator,
-                value: Value::String(String::from("%")),
-            }),
-            '(' => tokens.push(Token {
-                r#type: Type::OpenParen,
-                value: Value::String(String::from("(")),
-            }),
-            ')' => tokens.push(Token {
-                r#type: Type::CloseParen,
-                value: Value::String(String::from(")")),
-            }),
-            '-' => tokens.push(Token {
-                r#type: Type::Operator,
-                value: Value::String(String::from("-")),
-            }),
-            '*' => tokens.push(Token {
-                r#type: Type::Operator,
-                value: Value::String(String::from("*")),
-            }),
-            '/' => tokens.push(Token {
-                r#type: Type::Operator,
-                value: Value::String(String::from("/")),
-            }),
-            ';' => tokens.push(Token {
-                r#type: Type::Semi,
-                value: Value::String(String::from(";")),
-            }),
-            number if number.is_digit(10) => {
-                let mut full_number = String::from(char);
-
-                loop {
-                    let next_char = input.chars().nth(cursor + 1);
-
-                    if next_char.is_none() {
-                        break;
-                    }
+            '/' if cursor.peek_nth(1) == Some('/') => match lex_line_comment(&mut cursor) {
+                Some(doc) => (Type::DocComment, Value::String(doc)),
+                None => continue,
+            },
+            '/' if cursor.peek_nth(1) == Some('*') => {
+                skip_block_comment(&mut cursor)?;
+                continue;
+            }
+            '*' if cursor.peek_nth(1) == Some('*') => {
+                cursor.advance();
+                cursor.advance();
+                (Type::Operator, Value::String(String::from("**")))
+            }
+            '+' | '-' | '*' | '/' | '%' => {
+                cursor.advance();
+                (Type::Operator, Value::String(char.to_string()))
+            }
+            '{' => {
+                cursor.advance();
 
-                    match next_char.unwrap() {
-                        valid_char if valid_char.is_digit(10) => {
-                            full_number.push(next_char.unwrap());
-                            cursor += 1;
-                            continue;
-                        }
-                        ';' => break,
-                        ' ' => break,
-                        operator if operators.contains(&operator) => break,
-                        _ => panic!("Unable to read character at position {}", cursor + 1),
-                    }
+                if let Some(interpolation) = interpolations.last_mut() {
+                    interpolation.depth += 1;
                 }
 
-                let full_number = full_number
-                    .parse::<f64>()
-                    .expect("error parsing number at position");
-
-                tokens.push(Token {
-                    r#type: Type::Number,
-                    value: Value::Number(full_number),
-                });
+                symbol(Type::OpenBrace, "{")
             }
+            '}' if matches!(interpolations.last(), Some(interpolation) if interpolation.depth == 0) =>
+            {
+                cursor.advance();
 
-            '\"' => {
-                let mut full_statement = String::from(char);
-
-                // get full statement before classifying it
-                loop {
-                    let next_char = input.chars().nth(cursor + 1);
-
-                    match next_char {
-                        Some(next_char) => match next_char {
-                            ' ' => break,
-                            '\\' => break,
-                            valid_char if valid_char.is_ascii() => {
-                                full_statement.push(next_char);
-                                cursor += 1;
-
-                                if valid_char == '"' {
-                                    break;
-                                }
-
-                                continue;
-                            }
-                            _ => break,
-                        },
-                        None => break,
+                let interpolation = interpolations.pop().expect("checked by the match guard");
+                let (contents, interpolates) =
+                    lex_string(&mut cursor, interpolation.quote, interpolation.opening)?;
+
+                match interpolates {
+                    true => {
+                        interpolations.push(interpolation);
+                        (Type::TemplateMiddle, Value::String(contents))
                     }
+                    false => (Type::TemplateTail, Value::String(contents)),
                 }
+            }
+            '}' => {
+                cursor.advance();
 
-                match full_statement {
-                    string if string.is_string_literal() => tokens.push(Token {
-                        r#type: Type::String,
-                        value: Value::String(string),
-                    }),
-
-                    _ => panic!("failed to read string at position {}", cursor),
+                if let Some(interpolation) = interpolations.last_mut() {
+                    interpolation.depth -= 1;
                 }
+
+                symbol(Type::CloseBrace, "}")
             }
+            ',' | ':' | '[' | ']' | '(' | ')' | ';' => {
+                cursor.advance();
 
-            char if char.is_alphabetic() => {
-                let mut full_statement = String::from(char);
-
-                // get full statement before classifying it
-                loop {
-                    let next_char = input.chars().nth(cursor + 1);
-
-                    match next_char {
-                        Some(next_char) => match next_char {
-                            ' ' => break,
-                            operator if operators.contains(&operator) => break,
-                            valid_char
-                                if valid_char.is_ascii() && !operators.contains(&valid_char) =>
-                            {
-                                full_statement.push(next_char);
-                                cursor += 1;
-                                continue;
-                            }
-                            _ => break,
-                        },
-                        None => break,
+                let r#type = match char {
+                    ',' => Type::Comma,
+                    ':' => Type::Colon,
+                    '[' => Type::OpenBracket,
+                    ']' => Type::CloseBracket,
+                    '(' => Type::OpenParen,
+                    ')' => Type::CloseParen,
+                    _ => Type::Semi,
+                };
+
+                (r#type, Value::String(char.to_string()))
+            }
+            number if number.is_ascii_digit() => {
+                (Type::Number, Value::Number(lex_number(&mut cursor)?))
+            }
+            '\'' if is_label(&cursor, tokens.last()) => {
+                cursor.advance();
+                let name = cursor.eat_while(|char| char.is_alphanumeric() || char == '_');
+
+                (Type::Label, Value::String(name.to_string()))
+            }
+            '"' | '\'' => {
+                cursor.advance();
+
+                let opening = Span::new(start, cursor.position());
+                let (contents, interpolates) = lex_string(&mut cursor, char, opening)?;
+
+                match interpolates {
+                    true => {
+                        interpolations.push(OpenInterpolation {
+                            quote: char,
+                            opening,
+                            depth: 0,
+                        });
+                        (Type::TemplateHead, Value::String(contents))
                     }
+                    false => (Type::String, Value::String(contents)),
                 }
+            }
+            'r' if matches!(cursor.peek_nth(1), Some('"' | '#')) => {
+                (Type::String, Value::String(lex_raw_string(&mut cursor)?))
+            }
+            char if char.is_alphabetic() || char == '_' => {
+                let word = cursor.eat_while(|char| char.is_alphanumeric() || char == '_');
 
-                // statement can be either keyword, string or identifier
-                match full_statement.as_str() {
-                    "let" => tokens.push(Token {
-                        r#type: Type::Let,
-                        value: Value::String(String::from("let")),
-                    }),
-                    "const" => tokens.push(Token {
-                        r#type: Type::Const,
-                        value: Value::String(String::from("const")),
-                    }),
-                    "for" => tokens.push(Token {
-                        r#type: Type::For,
-                        value: Value::String(String::from("for")),
-                    }),
-                    "in" => tokens.push(Token {
-                        r#type: Type::In,
-                        value: Value::String(String::from("in")),
-                    }),
-                    "if" => tokens.push(Token {
-                        r#type: Type::If,
-                        value: Value::String(String::from("if")),
-                    }),
-                    "else" => tokens.push(Token {
-                        r#type: Type::Else,
-                        value: Value::String(String::from("else")),
-                    }),
-                    "fn" => tokens.push(Token {
-                        r#type: Type::Fn,
-                        value: Value::String(String::from("fn")),
-                    }),
-                    "while" => tokens.push(Token {
-                        r#type: Type::While,
-                        value: Value::String(String::from("while")),
-                    }),
-                    _ => tokens.push(Token {
-                        r#type: Type::Identifier,
-                        value: Value::String(full_statement),
-                    }),
-                }
+                // word can be either a keyword or an identifier
+                let r#type = match word {
+                    "let" => Type::Let,
+                    "const" => Type::Const,
+                    "for" => Type::For,
+                    "in" => Type::In,
+                    "if" => Type::If,
+                    "else" => Type::Else,
+                    "fn" => Type::Fn,
+                    "while" => Type::While,
+                    "return" => Type::Return,
+                    "break" => Type::Break,
+                    "continue" => Type::Continue,
+                    _ => Type::Identifier,
+                };
+
+                (r#type, Value::String(word.to_string()))
+            }
+            _ => {
+                cursor.advance();
+
+                return Err(LexError::new(
+                    LexErrorKind::UnexpectedCharacter,
+                    format!("Unexpected character {:?}", char),
+                    Span::new(start, cursor.position()),
+                ));
             }
-            _ => panic!("Unable to read character at position {}", cursor),
         };
 
-        cursor += 1;
+        tokens.push(Token {
+            r#type,
+            value,
+            span: Span::new(start, cursor.position()),
+        });
     }
 
+    if let Some(interpolation) = interpolations.pop() {
+        return Err(unterminated_string(interpolation.opening));
+    }
+
+    let end = cursor.position();
+
     tokens.push(Token {
         r#type: Type::EOF,
         value: Value::String(String::from("\0")),
+        span: Span::new(end, end),
     });
 
-    return Ok(tokens);
+    Ok(tokens)
+}
+
+fn symbol(r#type: Type, text: &str) -> (Type, Value) {
+    (r#type, Value::String(String::from(text)))
+}
+
+/// Tells a loop label such as `'outer` apart from a single-quoted string, the cursor
+/// being on the quote. Only the tokens before it decide: a label either follows
+/// `break`/`continue`, or starts a statement as `'name:`. So `'x: for me'` is a string
+/// after `=` or `(`, and a label at the start of a statement.
+fn is_label(cursor: &Cursor, previous: Option<&Token>) -> bool {
+    let rest = &cursor.rest()[1..];
+    let name_length = rest
+        .find(|char: char| !(char.is_alphanumeric() || char == '_'))
+        .unwrap_or(rest.len());
+    let name = &rest[..name_length];
+    let after_name = &rest[name_length..];
+
+    if !name.starts_with(|char: char| char.is_alphabetic() || char == '_')
+        || after_name.starts_with('\'')
+    {
+        return false;
+    }
+
+    match previous {
+        None => after_name.starts_with(':'),
+        Some(token) if matches!(token.r#type, Type::Break | Type::Continue) => true,
+        Some(token) => after_name.starts_with(':') && ends_statement(token, cursor.position()),
+    }
+}
+
+/// Whether a statement can start after `token`. Semicolons are optional after
+/// expressions, so a new line after a token that can end an expression starts one too,
+/// but a new line inside an open `(` or after an operator does not.
+fn ends_statement(token: &Token, position: Position) -> bool {
+    match token.r#type {
+        Type::Semi | Type::OpenBrace | Type::CloseBrace | Type::DocComment => true,
+        Type::Identifier
+        | Type::Number
+        | Type::String
+        | Type::TemplateTail
+        | Type::CloseParen
+        | Type::CloseBracket => token.span.end.line < position.line,
+        _ => false,
+    }
+}
+
+/// Lexes a numeric literal: decimals with an optional fraction and exponent
+/// (`12`, `1.5`, `.5`, `6.02e23`, `1E-3`), hexadecimal `0xFF`, binary `0b1010` and
+/// octal `0o17`. Digits can be grouped with `_` separators as in `1_000_000`.
+fn lex_number(cursor: &mut Cursor) -> Result<f64, LexError> {
+    let start = cursor.position();
+
+    let radix = match (cursor.peek(), cursor.peek_nth(1)) {
+        (Some('0'), Some('x')) => Some((16, "hexadecimal")),
+        (Some('0'), Some('b')) => Some((2, "binary")),
+        (Some('0'), Some('o')) => Some((8, "octal")),
+        _ => None,
+    };
+
+    if let Some((radix, name)) = radix {
+        return lex_radix_number(cursor, radix, name);
+    }
+
+    let mut literal = lex_digits(cursor)?;
+
+    // a dot is only part of the number when a digit follows, so `1..5` stays a range
+    if cursor.peek() == Some('.') && cursor.peek_nth(1).is_some_and(|char| char.is_ascii_digit()) {
+        cursor.advance();
+        literal.push('.');
+        literal.push_str(&lex_digits(cursor)?);
+    }
+
+    if matches!(cursor.peek(), Some('e' | 'E')) {
+        cursor.advance();
+        literal.push('e');
+
+        if let Some(sign @ ('+' | '-')) = cursor.peek() {
+            cursor.advance();
+            literal.push(sign);
+        }
+
+        let exponent = lex_digits(cursor)?;
+
+        if exponent.is_empty() {
+            return Err(invalid_number(
+                "Missing digits in the exponent of the number",
+                Span::new(start, cursor.position()),
+            ));
+        }
+
+        literal.push_str(&exponent);
+    }
+
+    check_number_end(cursor, start)?;
+
+    Ok(literal
+        .parse::<f64>()
+        .expect("validated decimal literal is a valid number"))
+}
+
+/// Lexes the digits of a `0x`, `0b` or `0o` literal, starting at the leading `0`.
+fn lex_radix_number(cursor: &mut Cursor, radix: u32, name: &str) -> Result<f64, LexError> {
+    let start = cursor.position();
+    cursor.advance();
+    let prefix = cursor.advance().expect("prefix checked by the caller");
+
+    let digits_start = cursor.position();
+    let digits = cursor.eat_while(|char| char.is_ascii_alphanumeric() || char == '_');
+    let span = Span::new(start, cursor.position());
+
+    if digits.chars().all(|char| char == '_') {
+        return Err(invalid_number(
+            format!("Missing digits after \"0{}\" in {} literal", prefix, name),
+            span,
+        ));
+    }
+
+    check_separators(digits, span)?;
+
+    let mut value = 0.0;
+
+    for (index, char) in digits.char_indices() {
+        if char == '_' {
+            continue;
+        }
+
+        let Some(digit) = char.to_digit(radix) else {
+            // digits are ascii, so byte and character offsets agree
+            let digit_start = Position::new(
+                digits_start.offset + index,
+                digits_start.line,
+                digits_start.column + index,
+            );
+            let digit_end = Position::new(
+                digit_start.offset + 1,
+                digit_start.line,
+                digit_start.column + 1,
+            );
+
+            return Err(invalid_number(
+                format!("Invalid digit {:?} in {} literal", char, name),
+                Span::new(digit_start, digit_end),
+            ));
+        };
+
+        value = value * radix as f64 + digit as f64;
+    }
+
+    Ok(value)
+}
+
+/// Consumes a run of decimal digits and `_` separators, returning the digits only.
+fn lex_digits(cursor: &mut Cursor) -> Result<String, LexError> {
+    let start = cursor.position();
+    let digits = cursor.eat_while(|char| char.is_ascii_digit() || char == '_');
+
+    check_separators(digits, Span::new(start, cursor.position()))?;
+
+    Ok(digits.replace('_', ""))
+}
+
+fn check_separators(digits: &str, span: Span) -> Result<(), LexError> {
+    if digits.starts_with('_') || digits.ends_with('_') || digits.contains("__") {
+        return Err(invalid_number(
+            "Digit separators (\"_\") must be placed between digits",
+            span,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Rejects letters glued to the end of a number, such as `12ab` or `1.5x`.
+fn check_number_end(cursor: &mut Cursor, start: Position) -> Result<(), LexError> {
+    match cursor.peek() {
+        Some(char) if char.is_alphanumeric() || char == '_' => {
+            cursor.advance();
+
+            Err(invalid_number(
+                format!("Unexpected character {:?} in number", char),
+                Span::new(start, cursor.position()),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn invalid_number(message: impl Into<String>, span: Span) -> LexError {
+    LexError::new(LexErrorKind::InvalidNumber, message, span)
+}
+
+/// Lexes the contents of a `"..."` or `'...'` string after its opening quote (or
+/// after the `}` of an interpolation) up to the closing `quote` or the next `${`,
+/// the returned flag tells whether an interpolation was opened. Strings may span
+/// several lines and support the escapes `\n`, `\t`, `\r`, `\0`, `\\`, `\"`, `\'`,
+/// `\$` and `\u{...}`.
+fn lex_string(cursor: &mut Cursor, quote: char, opening: Span) -> Result<(String, bool), LexError> {
+    let mut contents = String::new();
+
+    loop {
+        match cursor.peek() {
+            Some(char) if char == quote => {
+                cursor.advance();
+                return Ok((contents, false));
+            }
+            Some('$') if cursor.peek_nth(1) == Some('{') => {
+                cursor.advance();
+                cursor.advance();
+                return Ok((contents, true));
+            }
+            Some('\\') => contents.push(lex_escape(cursor)?),
+            Some(char) => {
+                contents.push(char);
+                cursor.advance();
+            }
+            None => return Err(unterminated_string(opening)),
+        }
+    }
+}
+
+fn lex_escape(cursor: &mut Cursor) -> Result<char, LexError> {
+    let start = cursor.position();
+    cursor.advance(); // consume "\"
+
+    let escaped = match cursor.advance() {
+        Some('n') => '\n',
+        Some('t') => '\t',
+        Some('r') => '\r',
+        Some('0') => '\0',
+        Some('\\') => '\\',
+        Some('"') => '"',
+        Some('\'') => '\'',
+        Some('$') => '$',
+        Some('u') => return lex_unicode_escape(cursor, start),
+        Some(char) => {
+            return Err(LexError::new(
+                LexErrorKind::InvalidString,
+                format!("Unknown escape sequence \"\\{}\"", char),
+                Span::new(start, cursor.position()),
+            ))
+        }
+        None => return Err(unterminated_string(Span::new(start, cursor.position()))),
+    };
+
+    Ok(escaped)
+}
+
+/// Lexes the `{...}` part of a `\u{...}` escape, `start` is the position of the backslash.
+fn lex_unicode_escape(cursor: &mut Cursor, start: Position) -> Result<char, LexError> {
+    let invalid_escape = |cursor: &Cursor| {
+        LexError::new(
+            LexErrorKind::InvalidString,
+            "Invalid unicode escape, expected \"\\u{...}\" with 1 to 6 hex digits",
+            Span::new(start, cursor.position()),
+        )
+    };
+
+    if !cursor.eat('{') {
+        return Err(invalid_escape(cursor));
+    }
+
+    let digits = cursor.eat_while(|char| char.is_ascii_hexdigit());
+
+    if digits.is_empty() || digits.len() > 6 || !cursor.eat('}') {
+        return Err(invalid_escape(cursor));
+    }
+
+    let code_point = u32::from_str_radix(digits, 16).expect("validated hex digits");
+
+    char::from_u32(code_point).ok_or_else(|| {
+        LexError::new(
+            LexErrorKind::InvalidString,
+            format!("\"{:X}\" is not a valid unicode code point", code_point),
+            Span::new(start, cursor.position()),
+        )
+    })
+}
+
+/// Lexes a raw string such as `r"C:\path"` or `r#"say "hi""#`. Escapes are not
+/// processed and the string ends at a quote followed by as many `#` as it opened with.
+fn lex_raw_string(cursor: &mut Cursor) -> Result<String, LexError> {
+    let start = cursor.position();
+    cursor.advance(); // consume "r"
+
+    let hashes = cursor.eat_while(|char| char == '#').len();
+
+    if !cursor.eat('"') {
+        return Err(LexError::new(
+            LexErrorKind::InvalidString,
+            "Expected \"\"\" to open the raw string",
+            Span::new(start, cursor.position()),
+        ));
+    }
+
+    let opening = Span::new(start, cursor.position());
+
+    let mut contents = String::new();
+
+    loop {
+        match cursor.advance() {
+            Some('"') => {
+                let mut closing = 0;
+
+                while closing < hashes && cursor.eat('#') {
+                    closing += 1;
+                }
+
+                if closing == hashes {
+                    return Ok(contents);
+                }
+
+                contents.push('"');
+                contents.extend(std::iter::repeat_n('#', closing));
+            }
+            Some(char) => contents.push(char),
+            None => return Err(unterminated_string(opening)),
+        }
+    }
+}
+
+/// Points at the opening quote, the end of the file is rarely where the quote is missing.
+fn unterminated_string(opening: Span) -> LexError {
+    LexError::new(
+        LexErrorKind::UnterminatedString,
+        "Unterminated string literal",
+        opening,
+    )
+}
+
+/// Skips a `//` comment up to the end of the line. `///` doc comments are returned
+/// with the leading space removed so the parser can attach them to declarations,
+/// while `////` and longer runs of slashes are ordinary comments.
+fn lex_line_comment(cursor: &mut Cursor) -> Option<String> {
+    cursor.advance();
+    cursor.advance(); // consume "//"
+
+    let is_doc = cursor.peek() == Some('/') && cursor.peek_nth(1) != Some('/');
+
+    if is_doc {
+        cursor.advance();
+    }
+
+    let text = cursor.eat_while(|char| char != '\n');
+
+    match is_doc {
+        true => Some(
+            text.strip_prefix(' ')
+                .unwrap_or(text)
+                .trim_end()
+                .to_string(),
+        ),
+        false => None,
+    }
+}
+
+/// Skips a `/* */` comment, block comments nest so `/* a /* b */ c */` is a single comment.
+fn skip_block_comment(cursor: &mut Cursor) -> Result<(), LexError> {
+    let start = cursor.position();
+    let mut depth = 0;
+
+    loop {
+        match (cursor.peek(), cursor.peek_nth(1)) {
+            (Some('/'), Some('*')) => {
+                cursor.advance();
+                cursor.advance();
+                depth += 1;
+            }
+            (Some('*'), Some('/')) => {
+                cursor.advance();
+                cursor.advance();
+                depth -= 1;
+
+                if depth == 0 {
+                    return Ok(());
+                }
+            }
+            (Some(_), _) => {
+                cursor.advance();
+            }
+            (None, _) => {
+                // point at the opening "/*" rather than at the end of the file
+                let opening_end = Position::new(start.offset + 2, start.line, start.column + 2);
+
+                return Err(LexError::new(
+                    LexErrorKind::UnterminatedComment,
+                    "Unterminated block comment",
+                    Span::new(start, opening_end),
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lex(source: &str) -> Vec<(Type, Value)> {
+        let tokens = tokenize(source).expect("source should lex");
+
+        tokens
+            .into_iter()
+            .filter(|token| token.r#type != Type::EOF)
+            .map(|token| (token.r#type, token.value))
+            .collect()
+    }
+
+    fn lex_error(source: &str) -> LexError {
+        tokenize(source).expect_err("source should not lex")
+    }
+
+    fn text(r#type: Type, text: &str) -> (Type, Value) {
+        (r#type, Value::String(text.to_string()))
+    }
+
+    fn number(value: f64) -> (Type, Value) {
+        (Type::Number, Value::Number(value))
+    }
+
+    #[test]
+    fn lexes_utf8_identifiers() {
+        assert_eq!(
+            lex("let café = 日本_2;"),
+            vec![
+                text(Type::Let, "let"),
+                text(Type::Identifier, "café"),
+                text(Type::Equals, "="),
+                text(Type::Identifier, "日本_2"),
+                text(Type::Semi, ";"),
+            ]
+        );
+    }
+
+    #[test]
+    fn spans_count_characters_and_lines() {
+        let tokens = tokenize("let é = 1;\n  x").unwrap();
+
+        // "é" takes two bytes but a single column
+        assert_eq!(
+            tokens[3].span,
+            Span::new(Position::new(9, 1, 9), Position::new(10, 1, 10))
+        );
+        assert_eq!(
+            tokens[5].span,
+            Span::new(Position::new(14, 2, 3), Position::new(15, 2, 4))
+        );
+        assert_eq!(tokens[6].r#type, Type::EOF);
+        assert_eq!(tokens[6].span.start, Position::new(15, 2, 4));
+    }
+
+    #[test]
+    fn reports_unexpected_characters() {
+        let error = lex_error("let x = 1 # 2;");
+
+        assert_eq!(error.kind, LexErrorKind::UnexpectedCharacter);
+        assert_eq!(error.span.start, Position::new(10, 1, 11));
+    }
+
+    #[test]
+    fn lexes_strings() {
+        assert_eq!(
+            lex(
+                r##""a\tb\n" 'it\'s' "\u{1F600}" r"C:\new" r#"say "hi""# "two
+lines""##
+            ),
+            vec![
+                text(Type::String, "a\tb\n"),
+                text(Type::String, "it's"),
+                text(Type::String, "😀"),
+                text(Type::String, r"C:\new"),
+                text(Type::String, r#"say "hi""#),
+                text(Type::String, "two\nlines"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_unterminated_strings_at_the_opening_quote() {
+        let error = lex_error("let s = \"abc\n");
+
+        assert_eq!(error.kind, LexErrorKind::UnterminatedString);
+        assert_eq!(error.span.start, Position::new(8, 1, 9));
+    }
+
+    #[test]
+    fn reports_invalid_escapes() {
+        assert_eq!(lex_error(r#""\q""#).kind, LexErrorKind::InvalidString);
+        assert_eq!(
+            lex_error(r#""\u{110000}""#).kind,
+            LexErrorKind::InvalidString
+        );
+    }
+
+    #[test]
+    fn skips_comments_and_keeps_doc_comments() {
+        assert_eq!(
+            lex("// note\n/* outer /* inner */ still */ x //// not doc\n/// the doc  \nlet"),
+            vec![
+                text(Type::Identifier, "x"),
+                text(Type::DocComment, "the doc"),
+                text(Type::Let, "let"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_unterminated_block_comments() {
+        let error = lex_error("x /* a /* b */");
+
+        assert_eq!(error.kind, LexErrorKind::UnterminatedComment);
+        assert_eq!(
+            error.span,
+            Span::new(Position::new(2, 1, 3), Position::new(4, 1, 5))
+        );
+    }
+
+    #[test]
+    fn splits_interpolated_strings() {
+        assert_eq!(
+            lex(r#""a ${x} b ${ {k: 1}.k } c""#),
+            vec![
+                text(Type::TemplateHead, "a "),
+                text(Type::Identifier, "x"),
+                text(Type::TemplateMiddle, " b "),
+                text(Type::OpenBrace, "{"),
+                text(Type::Identifier, "k"),
+                text(Type::Colon, ":"),
+                number(1.0),
+                text(Type::CloseBrace, "}"),
+                text(Type::Dot, "."),
+                text(Type::Identifier, "k"),
+                text(Type::TemplateTail, " c"),
+            ]
+        );
+    }
+
+    #[test]
+    fn lexes_numbers() {
+        assert_eq!(
+            lex("42 2.5 .5 6.02e23 1E-3 1_000_000 0xFF 0b1010 0o17"),
+            vec![
+                number(42.0),
+                number(2.5),
+                number(0.5),
+                number(6.02e23),
+                number(1e-3),
+                number(1_000_000.0),
+                number(255.0),
+                number(10.0),
+                number(15.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_ranges_apart_from_fractions() {
+        assert_eq!(
+            lex("1..5 1..=5"),
+            vec![
+                number(1.0),
+                text(Type::Interval, ".."),
+                number(5.0),
+                number(1.0),
+                text(Type::InclusiveInterval, "..="),
+                number(5.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_misplaced_digit_separators() {
+        for source in ["1_", "1__0", "1.5_", "0x_FF_"] {
+            let error = lex_error(source);
+
+            assert_eq!(error.kind, LexErrorKind::InvalidNumber, "{}", source);
+            assert_eq!(
+                error.message,
+                "Digit separators (\"_\") must be placed between digits"
+            );
+        }
+    }
+
+    #[test]
+    fn reports_missing_digits() {
+        let error = lex_error("0x;");
+
+        assert_eq!(error.kind, LexErrorKind::InvalidNumber);
+        assert_eq!(
+            error.message,
+            "Missing digits after \"0x\" in hexadecimal literal"
+        );
+        assert_eq!(
+            error.span,
+            Span::new(Position::new(0, 1, 1), Position::new(2, 1, 3))
+        );
+
+        for source in ["1e", "1e+", "2.5E-"] {
+            let error = lex_error(source);
+
+            assert_eq!(error.kind, LexErrorKind::InvalidNumber, "{}", source);
+            assert_eq!(
+                error.message,
+                "Missing digits in the exponent of the number"
+            );
+        }
+    }
+
+    #[test]
+    fn reports_invalid_radix_digits_at_the_digit() {
+        let error = lex_error("x = 0b102;");
+
+        assert_eq!(error.kind, LexErrorKind::InvalidNumber);
+        assert_eq!(error.message, "Invalid digit '2' in binary literal");
+        assert_eq!(
+            error.span,
+            Span::new(Position::new(8, 1, 9), Position::new(9, 1, 10))
+        );
+
+        assert_eq!(
+            lex_error("0o78").message,
+            "Invalid digit '8' in octal literal"
+        );
+        assert_eq!(
+            lex_error("0xFG").message,
+            "Invalid digit 'G' in hexadecimal literal"
+        );
+    }
+
+    #[test]
+    fn reports_letters_glued_to_numbers() {
+        let error = lex_error("12ab");
+
+        assert_eq!(error.kind, LexErrorKind::InvalidNumber);
+        assert_eq!(error.message, "Unexpected character 'a' in number");
+        assert_eq!(
+            error.span,
+            Span::new(Position::new(0, 1, 1), Position::new(3, 1, 4))
+        );
+
+        assert_eq!(
+            lex_error("1.5x").message,
+            "Unexpected character 'x' in number"
+        );
+    }
+
+    #[test]
+    fn lexes_labels_on_loops_and_loop_control() {
+        assert_eq!(
+            lex("'outer: for i in xs { break 'outer; }"),
+            vec![
+                text(Type::Label, "outer"),
+                text(Type::Colon, ":"),
+                text(Type::For, "for"),
+                text(Type::Identifier, "i"),
+                text(Type::In, "in"),
+                text(Type::Identifier, "xs"),
+                text(Type::OpenBrace, "{"),
+                text(Type::Break, "break"),
+                text(Type::Label, "outer"),
+                text(Type::Semi, ";"),
+                text(Type::CloseBrace, "}"),
+            ]
+        );
+        assert_eq!(
+            lex("'outer: for x in [1] { let s = 'multi\nline'; print(s); }"),
+            vec![
+                text(Type::Label, "outer"),
+                text(Type::Colon, ":"),
+                text(Type::For, "for"),
+                text(Type::Identifier, "x"),
+                text(Type::In, "in"),
+                text(Type::OpenBracket, "["),
+                number(1.0),
+                text(Type::CloseBracket, "]"),
+                text(Type::OpenBrace, "{"),
+                text(Type::Let, "let"),
+                text(Type::Identifier, "s"),
+                text(Type::Equals, "="),
+                text(Type::String, "multi\nline"),
+                text(Type::Semi, ";"),
+                text(Type::Identifier, "print"),
+                text(Type::OpenParen, "("),
+                text(Type::Identifier, "s"),
+                text(Type::CloseParen, ")"),
+                text(Type::Semi, ";"),
+                text(Type::CloseBrace, "}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_labels_on_loops_containing_strings() {
+        let tokens = lex("f()\n'w: while x { print('a'); continue 'w; }");
+
+        assert_eq!(tokens[3], text(Type::Label, "w"));
+        assert_eq!(tokens[10], text(Type::String, "a"));
+        assert_eq!(tokens[14], text(Type::Label, "w"));
+    }
+
+    #[test]
+    fn does_not_lex_strings_as_labels() {
+        assert_eq!(
+            lex("print('note: while waiting'); let s = 'x: for me';"),
+            vec![
+                text(Type::Identifier, "print"),
+                text(Type::OpenParen, "("),
+                text(Type::String, "note: while waiting"),
+                text(Type::CloseParen, ")"),
+                text(Type::Semi, ";"),
+                text(Type::Let, "let"),
+                text(Type::Identifier, "s"),
+                text(Type::Equals, "="),
+                text(Type::String, "x: for me"),
+                text(Type::Semi, ";"),
+            ]
+        );
+        assert_eq!(
+            lex("print(\n'note: while\nwaiting')"),
+            vec![
+                text(Type::Identifier, "print"),
+                text(Type::OpenParen, "("),
+                text(Type::String, "note: while\nwaiting"),
+                text(Type::CloseParen, ")"),
+            ]
+        );
+    }
 }
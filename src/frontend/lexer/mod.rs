@@ -1,3 +1,4 @@
+pub(crate) mod cursor;
 pub(crate) mod error;
 
 pub(crate) mod lib;
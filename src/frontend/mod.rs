@@ -1,2 +1,3 @@
 pub(crate) mod lexer;
 pub(crate) mod parser;
+pub(crate) mod span;
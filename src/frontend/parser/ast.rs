@@ -1,91 +1,215 @@
-use crate::frontend::lexer::lib::Value;
+use std::{fmt, rc::Rc};
+
+use crate::frontend::span::Span;
 
 #[derive(Debug)]
-pub struct AST<'a> {
-    pub(crate) kind: &'a str,
+pub struct AST {
     pub(crate) statements: Vec<ASTStatement>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct ASTStatement {
+    pub(crate) kind: ASTStatementKind,
+    pub(crate) span: Span,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ASTStatementKind {
     ExpressionStatement(ASTExpression),
     VariableDeclaration(VariableDeclaration),
     FunctionDeclaration(FunctionDeclaration),
     ConditionalStatement(ConditionalStatement),
-    LoopStatement(LoopStatement),
+    ForStatement(ForStatement),
+    WhileStatement(WhileStatement),
+    ReturnStatement(ReturnStatement),
+    BreakStatement(BreakStatement),
+    ContinueStatement(ContinueStatement),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReturnStatement {
+    pub(crate) value: Option<ASTExpression>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BreakStatement {
+    pub(crate) label: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContinueStatement {
+    pub(crate) label: Option<String>,
+}
+
+/// `for identifier in iterable { body }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForStatement {
+    pub(crate) label: Option<String>,
+    pub(crate) identifier: String,
+    pub(crate) iterable: ASTExpression,
+    pub(crate) body: Vec<ASTStatement>,
 }
 
+/// `while condition { body }`
 #[derive(Debug, Clone, PartialEq)]
-pub struct LoopStatement {
+pub struct WhileStatement {
+    pub(crate) label: Option<String>,
+    pub(crate) condition: ASTExpression,
     pub(crate) body: Vec<ASTStatement>,
-    pub(crate) identifier: Option<String>,
-    pub(crate) condition: Option<ASTExpression>,
-    pub(crate) interval: Option<ASTExpression>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ConditionalStatement {
-    pub(crate) condition: Option<ASTExpression>,
+    pub(crate) condition: ASTExpression,
     pub(crate) consequence: Vec<ASTStatement>,
-    pub(crate) alternate: Option<Box<ASTStatement>>,
+    pub(crate) alternate: Option<Alternate>,
+}
+
+/// What runs when the condition of an `if` is falsy.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Alternate {
+    ElseIf(Box<ConditionalStatement>),
+    Else(Vec<ASTStatement>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct VariableDeclaration {
     pub(crate) constant: bool,
-    pub(crate) identifier: Value,
+    pub(crate) identifier: String,
     pub(crate) value: Option<ASTExpression>,
+    /// Text of the `///` comments right before the declaration, for tooling.
+    #[allow(dead_code)]
+    pub(crate) doc: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct FunctionDeclaration {
     pub(crate) identifier: String,
-    pub(crate) parameters: Vec<String>,
-    pub(crate) body: Vec<ASTStatement>,
+    pub(crate) parameters: Vec<Parameter>,
+    /// Shared with the function values created from the declaration, so running it
+    /// never copies the body.
+    pub(crate) body: Rc<Vec<ASTStatement>>,
+    /// Text of the `///` comments right before the declaration, for tooling.
+    #[allow(dead_code)]
+    pub(crate) doc: Option<String>,
 }
 
+/// A function parameter. `default` is evaluated when the argument is missing and a
+/// `rest` parameter, `...name`, collects the remaining arguments into an array.
 #[derive(Debug, Clone, PartialEq)]
-pub struct VariableAssignment {
-    pub(crate) assignee: Box<ASTExpression>,
-    pub(crate) value: Box<ASTExpression>,
+pub struct Parameter {
+    pub(crate) name: String,
+    pub(crate) default: Option<ASTExpression>,
+    pub(crate) rest: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ASTExpression {
+    pub(crate) kind: ASTExpressionKind,
+    pub(crate) span: Span,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ASTExpressionKind {
-    Identifier,
-    AssignmentExpression,
-    MemberExpression,
-    CallExpression,
-    BinaryExpression,
+    Identifier(String),
+    AssignmentExpression(VariableAssignment),
+    MemberExpression(MemberExpression),
+    CallExpression(CallExpression),
+    BinaryExpression(BinaryExpression),
+    LogicalExpression(LogicalExpression),
+    UnaryExpression(UnaryExpression),
+    FunctionExpression(FunctionExpression),
 
     // literals
-    StringLiteral,
-    ObjectLiteral,
-    NumericLiteral,
+    StringLiteral(String),
+    InterpolatedString(Vec<InterpolationPart>),
+    ObjectLiteral(Vec<Property>),
+    ArrayLiteral(Vec<ASTExpression>),
+    NumericLiteral(f64),
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct ASTExpression {
-    pub(crate) kind: ASTExpressionKind,
-    pub(crate) body: ASTExpressionBody,
+pub struct VariableAssignment {
+    pub(crate) assignee: Box<ASTExpression>,
+    pub(crate) value: Box<ASTExpression>,
+}
+
+/// A property of an object literal, `value` is `None` for the shorthand `{ name }`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Property {
+    pub(crate) key: String,
+    pub(crate) value: Option<ASTExpression>,
 }
 
+/// A piece of an interpolated string such as `"total: ${a + b}"`.
 #[derive(Debug, Clone, PartialEq)]
-pub enum ASTExpressionBody {
-    Value(Value),
-    BinaryExpressionBody(BinaryExpression),
-    AssignmentExpressionBody(VariableAssignment),
-    CallExpressionBody(CallExpression),
-    MemberExpressionBody(MemberExpression),
+pub enum InterpolationPart {
+    Literal(String),
+    Expression(ASTExpression),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct BinaryExpression {
     pub(crate) left: Box<ASTExpression>,
-    pub(crate) operator: Value,
+    pub(crate) operator: BinaryOperator,
     pub(crate) right: Box<ASTExpression>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Exponent,
+    Equal,
+    NotEqual,
+    LessThan,
+    LessEqual,
+    GreaterThan,
+    GreaterEqual,
+    Range,
+    InclusiveRange,
+}
+
+/// `&&` and `||`, which only evaluate their right operand when the left one does
+/// not decide the result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogicalExpression {
+    pub(crate) left: Box<ASTExpression>,
+    pub(crate) operator: LogicalOperator,
+    pub(crate) right: Box<ASTExpression>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogicalOperator {
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnaryExpression {
+    pub(crate) operator: UnaryOperator,
+    pub(crate) argument: Box<ASTExpression>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOperator {
+    Not,
+    BitwiseNot,
+    Negate,
+    Plus,
+}
+
+/// An anonymous function, `fn (x) { x * 2 }` or `(x) => x * 2`. The body of an
+/// arrow function with an expression body is a single expression statement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionExpression {
+    pub(crate) parameters: Vec<Parameter>,
+    pub(crate) body: Rc<Vec<ASTStatement>>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct CallExpression {
     pub(crate) arguments: Vec<ASTExpression>,
@@ -95,11 +219,48 @@ pub struct CallExpression {
 #[derive(Debug, Clone, PartialEq)]
 pub struct MemberExpression {
     pub(crate) object: Box<ASTExpression>,
-    pub(crate) property: Box<ASTExpression>,
-    pub(crate) computed: bool,
+    pub(crate) property: MemberProperty,
 }
 
+/// `object.name` or `object[expression]`.
 #[derive(Debug, Clone, PartialEq)]
-pub struct ASTStatement {
-    pub(crate) kind: ASTStatementKind,
+pub enum MemberProperty {
+    Named(String),
+    Computed(Box<ASTExpression>),
+}
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = match self {
+            BinaryOperator::Add => "+",
+            BinaryOperator::Subtract => "-",
+            BinaryOperator::Multiply => "*",
+            BinaryOperator::Divide => "/",
+            BinaryOperator::Remainder => "%",
+            BinaryOperator::Exponent => "**",
+            BinaryOperator::Equal => "==",
+            BinaryOperator::NotEqual => "!=",
+            BinaryOperator::LessThan => "<",
+            BinaryOperator::LessEqual => "<=",
+            BinaryOperator::GreaterThan => ">",
+            BinaryOperator::GreaterEqual => ">=",
+            BinaryOperator::Range => "..",
+            BinaryOperator::InclusiveRange => "..=",
+        };
+
+        write!(f, "{}", symbol)
+    }
+}
+
+impl fmt::Display for UnaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = match self {
+            UnaryOperator::Not => "!",
+            UnaryOperator::BitwiseNot => "~",
+            UnaryOperator::Negate => "-",
+            UnaryOperator::Plus => "+",
+        };
+
+        write!(f, "{}", symbol)
+    }
 }
@@ -0,0 +1,60 @@
+use crate::{
+    diagnostics::{codes, diagnostic::Diagnostic},
+    frontend::span::Span,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParseErrorKind {
+    UnexpectedToken,
+    ExpectedIdentifier,
+    MissingConstantValue,
+    ReturnOutsideFunction,
+    LoopControlOutsideLoop,
+    UndefinedLabel,
+    InvalidParameter,
+    InvalidAssignmentTarget,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub(crate) kind: ParseErrorKind,
+    pub(crate) message: String,
+    pub(crate) span: Span,
+}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+impl ParseError {
+    pub fn new(kind: ParseErrorKind, message: impl Into<String>, span: Span) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            span,
+        }
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let code = match self.kind {
+            ParseErrorKind::UnexpectedToken => &codes::UNEXPECTED_TOKEN,
+            ParseErrorKind::ExpectedIdentifier => &codes::EXPECTED_IDENTIFIER,
+            ParseErrorKind::MissingConstantValue => &codes::MISSING_CONSTANT_VALUE,
+            ParseErrorKind::ReturnOutsideFunction => &codes::RETURN_OUTSIDE_FUNCTION,
+            ParseErrorKind::LoopControlOutsideLoop => &codes::LOOP_CONTROL_OUTSIDE_LOOP,
+            ParseErrorKind::UndefinedLabel => &codes::UNDEFINED_LABEL,
+            ParseErrorKind::InvalidParameter => &codes::INVALID_PARAMETER,
+            ParseErrorKind::InvalidAssignmentTarget => &codes::INVALID_ASSIGNMENT_TARGET,
+        };
+
+        let diagnostic = Diagnostic::error(self.message.clone())
+            .with_code(code)
+            .with_span(Some(self.span))
+            .with_label(code.title);
+
+        match self.kind {
+            ParseErrorKind::MissingConstantValue => {
+                diagnostic.with_help("provide a value, e.g. `const name = value;`")
+            }
+            _ => diagnostic,
+        }
+    }
+}
@@ -0,0 +1,518 @@
+//! Owning transformation of the AST. Implement `Fold` to build a new tree out of the
+//! old one, for example to replace `2 * 3` with `6`. Every method takes a node by value
+//! and returns its replacement, the default implementations rebuild the node from its
+//! folded children using the matching `walk_*` function.
+
+use std::rc::Rc;
+
+use super::ast::{
+    ASTExpression, ASTExpressionKind, ASTStatement, ASTStatementKind, Alternate, BinaryExpression,
+    BreakStatement, CallExpression, ConditionalStatement, ContinueStatement, ForStatement,
+    FunctionDeclaration, FunctionExpression, InterpolationPart, LogicalExpression,
+    MemberExpression, MemberProperty, Parameter, Property, ReturnStatement, UnaryExpression,
+    VariableAssignment, VariableDeclaration, WhileStatement, AST,
+};
+
+pub trait Fold {
+    fn fold_program(&mut self, program: AST) -> AST {
+        walk_program(self, program)
+    }
+
+    fn fold_statement(&mut self, statement: ASTStatement) -> ASTStatement {
+        walk_statement(self, statement)
+    }
+
+    fn fold_variable_declaration(
+        &mut self,
+        declaration: VariableDeclaration,
+    ) -> VariableDeclaration {
+        walk_variable_declaration(self, declaration)
+    }
+
+    fn fold_function_declaration(
+        &mut self,
+        declaration: FunctionDeclaration,
+    ) -> FunctionDeclaration {
+        walk_function_declaration(self, declaration)
+    }
+
+    fn fold_parameter(&mut self, parameter: Parameter) -> Parameter {
+        walk_parameter(self, parameter)
+    }
+
+    fn fold_conditional_statement(
+        &mut self,
+        conditional: ConditionalStatement,
+    ) -> ConditionalStatement {
+        walk_conditional_statement(self, conditional)
+    }
+
+    fn fold_for_statement(&mut self, for_statement: ForStatement) -> ForStatement {
+        walk_for_statement(self, for_statement)
+    }
+
+    fn fold_while_statement(&mut self, while_statement: WhileStatement) -> WhileStatement {
+        walk_while_statement(self, while_statement)
+    }
+
+    fn fold_return_statement(&mut self, return_statement: ReturnStatement) -> ReturnStatement {
+        walk_return_statement(self, return_statement)
+    }
+
+    fn fold_break_statement(&mut self, break_statement: BreakStatement) -> BreakStatement {
+        break_statement
+    }
+
+    fn fold_continue_statement(
+        &mut self,
+        continue_statement: ContinueStatement,
+    ) -> ContinueStatement {
+        continue_statement
+    }
+
+    /// Override this method to replace an expression with one of a different kind,
+    /// the methods for the individual expressions can only rebuild the same kind.
+    fn fold_expression(&mut self, expression: ASTExpression) -> ASTExpression {
+        walk_expression(self, expression)
+    }
+
+    fn fold_binary_expression(&mut self, binary: BinaryExpression) -> BinaryExpression {
+        walk_binary_expression(self, binary)
+    }
+
+    fn fold_logical_expression(&mut self, logical: LogicalExpression) -> LogicalExpression {
+        walk_logical_expression(self, logical)
+    }
+
+    fn fold_assignment_expression(&mut self, assignment: VariableAssignment) -> VariableAssignment {
+        walk_assignment_expression(self, assignment)
+    }
+
+    fn fold_unary_expression(&mut self, unary: UnaryExpression) -> UnaryExpression {
+        walk_unary_expression(self, unary)
+    }
+
+    fn fold_call_expression(&mut self, call: CallExpression) -> CallExpression {
+        walk_call_expression(self, call)
+    }
+
+    fn fold_member_expression(&mut self, member: MemberExpression) -> MemberExpression {
+        walk_member_expression(self, member)
+    }
+
+    fn fold_function_expression(&mut self, function: FunctionExpression) -> FunctionExpression {
+        walk_function_expression(self, function)
+    }
+
+    fn fold_property(&mut self, property: Property) -> Property {
+        walk_property(self, property)
+    }
+
+    fn fold_interpolation_part(&mut self, part: InterpolationPart) -> InterpolationPart {
+        walk_interpolation_part(self, part)
+    }
+}
+
+pub fn walk_program<F: Fold + ?Sized>(folder: &mut F, program: AST) -> AST {
+    AST {
+        statements: fold_statements(folder, program.statements),
+    }
+}
+
+pub fn walk_statement<F: Fold + ?Sized>(folder: &mut F, statement: ASTStatement) -> ASTStatement {
+    let kind = match statement.kind {
+        ASTStatementKind::ExpressionStatement(expression) => {
+            ASTStatementKind::ExpressionStatement(folder.fold_expression(expression))
+        }
+        ASTStatementKind::VariableDeclaration(declaration) => {
+            ASTStatementKind::VariableDeclaration(folder.fold_variable_declaration(declaration))
+        }
+        ASTStatementKind::FunctionDeclaration(declaration) => {
+            ASTStatementKind::FunctionDeclaration(folder.fold_function_declaration(declaration))
+        }
+        ASTStatementKind::ConditionalStatement(conditional) => {
+            ASTStatementKind::ConditionalStatement(folder.fold_conditional_statement(conditional))
+        }
+        ASTStatementKind::ForStatement(for_statement) => {
+            ASTStatementKind::ForStatement(folder.fold_for_statement(for_statement))
+        }
+        ASTStatementKind::WhileStatement(while_statement) => {
+            ASTStatementKind::WhileStatement(folder.fold_while_statement(while_statement))
+        }
+        ASTStatementKind::ReturnStatement(return_statement) => {
+            ASTStatementKind::ReturnStatement(folder.fold_return_statement(return_statement))
+        }
+        ASTStatementKind::BreakStatement(break_statement) => {
+            ASTStatementKind::BreakStatement(folder.fold_break_statement(break_statement))
+        }
+        ASTStatementKind::ContinueStatement(continue_statement) => {
+            ASTStatementKind::ContinueStatement(folder.fold_continue_statement(continue_statement))
+        }
+    };
+
+    ASTStatement {
+        kind,
+        span: statement.span,
+    }
+}
+
+pub fn walk_variable_declaration<F: Fold + ?Sized>(
+    folder: &mut F,
+    declaration: VariableDeclaration,
+) -> VariableDeclaration {
+    VariableDeclaration {
+        value: declaration.value.map(|value| folder.fold_expression(value)),
+        ..declaration
+    }
+}
+
+pub fn walk_function_declaration<F: Fold + ?Sized>(
+    folder: &mut F,
+    declaration: FunctionDeclaration,
+) -> FunctionDeclaration {
+    FunctionDeclaration {
+        parameters: fold_parameters(folder, declaration.parameters),
+        body: fold_shared_statements(folder, declaration.body),
+        ..declaration
+    }
+}
+
+pub fn walk_parameter<F: Fold + ?Sized>(folder: &mut F, parameter: Parameter) -> Parameter {
+    Parameter {
+        default: parameter
+            .default
+            .map(|default| folder.fold_expression(default)),
+        ..parameter
+    }
+}
+
+pub fn walk_conditional_statement<F: Fold + ?Sized>(
+    folder: &mut F,
+    conditional: ConditionalStatement,
+) -> ConditionalStatement {
+    ConditionalStatement {
+        condition: folder.fold_expression(conditional.condition),
+        consequence: fold_statements(folder, conditional.consequence),
+        alternate: conditional.alternate.map(|alternate| match alternate {
+            Alternate::ElseIf(conditional) => {
+                Alternate::ElseIf(Box::new(folder.fold_conditional_statement(*conditional)))
+            }
+            Alternate::Else(block) => Alternate::Else(fold_statements(folder, block)),
+        }),
+    }
+}
+
+pub fn walk_for_statement<F: Fold + ?Sized>(
+    folder: &mut F,
+    for_statement: ForStatement,
+) -> ForStatement {
+    ForStatement {
+        iterable: folder.fold_expression(for_statement.iterable),
+        body: fold_statements(folder, for_statement.body),
+        ..for_statement
+    }
+}
+
+pub fn walk_while_statement<F: Fold + ?Sized>(
+    folder: &mut F,
+    while_statement: WhileStatement,
+) -> WhileStatement {
+    WhileStatement {
+        condition: folder.fold_expression(while_statement.condition),
+        body: fold_statements(folder, while_statement.body),
+        ..while_statement
+    }
+}
+
+pub fn walk_return_statement<F: Fold + ?Sized>(
+    folder: &mut F,
+    return_statement: ReturnStatement,
+) -> ReturnStatement {
+    ReturnStatement {
+        value: return_statement
+            .value
+            .map(|value| folder.fold_expression(value)),
+    }
+}
+
+pub fn walk_expression<F: Fold + ?Sized>(
+    folder: &mut F,
+    expression: ASTExpression,
+) -> ASTExpression {
+    let kind = match expression.kind {
+        ASTExpressionKind::AssignmentExpression(assignment) => {
+            ASTExpressionKind::AssignmentExpression(folder.fold_assignment_expression(assignment))
+        }
+        ASTExpressionKind::MemberExpression(member) => {
+            ASTExpressionKind::MemberExpression(folder.fold_member_expression(member))
+        }
+        ASTExpressionKind::CallExpression(call) => {
+            ASTExpressionKind::CallExpression(folder.fold_call_expression(call))
+        }
+        ASTExpressionKind::BinaryExpression(binary) => {
+            ASTExpressionKind::BinaryExpression(folder.fold_binary_expression(binary))
+        }
+        ASTExpressionKind::LogicalExpression(logical) => {
+            ASTExpressionKind::LogicalExpression(folder.fold_logical_expression(logical))
+        }
+        ASTExpressionKind::UnaryExpression(unary) => {
+            ASTExpressionKind::UnaryExpression(folder.fold_unary_expression(unary))
+        }
+        ASTExpressionKind::FunctionExpression(function) => {
+            ASTExpressionKind::FunctionExpression(folder.fold_function_expression(function))
+        }
+        ASTExpressionKind::InterpolatedString(parts) => ASTExpressionKind::InterpolatedString(
+            parts
+                .into_iter()
+                .map(|part| folder.fold_interpolation_part(part))
+                .collect(),
+        ),
+        ASTExpressionKind::ObjectLiteral(properties) => ASTExpressionKind::ObjectLiteral(
+            properties
+                .into_iter()
+                .map(|property| folder.fold_property(property))
+                .collect(),
+        ),
+        ASTExpressionKind::ArrayLiteral(elements) => {
+            ASTExpressionKind::ArrayLiteral(fold_expressions(folder, elements))
+        }
+        leaf => leaf,
+    };
+
+    ASTExpression {
+        kind,
+        span: expression.span,
+    }
+}
+
+pub fn walk_binary_expression<F: Fold + ?Sized>(
+    folder: &mut F,
+    binary: BinaryExpression,
+) -> BinaryExpression {
+    BinaryExpression {
+        left: Box::new(folder.fold_expression(*binary.left)),
+        operator: binary.operator,
+        right: Box::new(folder.fold_expression(*binary.right)),
+    }
+}
+
+pub fn walk_logical_expression<F: Fold + ?Sized>(
+    folder: &mut F,
+    logical: LogicalExpression,
+) -> LogicalExpression {
+    LogicalExpression {
+        left: Box::new(folder.fold_expression(*logical.left)),
+        operator: logical.operator,
+        right: Box::new(folder.fold_expression(*logical.right)),
+    }
+}
+
+pub fn walk_assignment_expression<F: Fold + ?Sized>(
+    folder: &mut F,
+    assignment: VariableAssignment,
+) -> VariableAssignment {
+    VariableAssignment {
+        assignee: Box::new(folder.fold_expression(*assignment.assignee)),
+        value: Box::new(folder.fold_expression(*assignment.value)),
+    }
+}
+
+pub fn walk_unary_expression<F: Fold + ?Sized>(
+    folder: &mut F,
+    unary: UnaryExpression,
+) -> UnaryExpression {
+    UnaryExpression {
+        operator: unary.operator,
+        argument: Box::new(folder.fold_expression(*unary.argument)),
+    }
+}
+
+pub fn walk_call_expression<F: Fold + ?Sized>(
+    folder: &mut F,
+    call: CallExpression,
+) -> CallExpression {
+    CallExpression {
+        caller: Box::new(folder.fold_expression(*call.caller)),
+        arguments: fold_expressions(folder, call.arguments),
+    }
+}
+
+pub fn walk_member_expression<F: Fold + ?Sized>(
+    folder: &mut F,
+    member: MemberExpression,
+) -> MemberExpression {
+    let property = match member.property {
+        MemberProperty::Computed(property) => {
+            MemberProperty::Computed(Box::new(folder.fold_expression(*property)))
+        }
+        named => named,
+    };
+
+    MemberExpression {
+        object: Box::new(folder.fold_expression(*member.object)),
+        property,
+    }
+}
+
+pub fn walk_function_expression<F: Fold + ?Sized>(
+    folder: &mut F,
+    function: FunctionExpression,
+) -> FunctionExpression {
+    FunctionExpression {
+        parameters: fold_parameters(folder, function.parameters),
+        body: fold_shared_statements(folder, function.body),
+    }
+}
+
+pub fn walk_property<F: Fold + ?Sized>(folder: &mut F, property: Property) -> Property {
+    Property {
+        key: property.key,
+        value: property.value.map(|value| folder.fold_expression(value)),
+    }
+}
+
+pub fn walk_interpolation_part<F: Fold + ?Sized>(
+    folder: &mut F,
+    part: InterpolationPart,
+) -> InterpolationPart {
+    match part {
+        InterpolationPart::Expression(expression) => {
+            InterpolationPart::Expression(folder.fold_expression(expression))
+        }
+        literal => literal,
+    }
+}
+
+fn fold_statements<F: Fold + ?Sized>(
+    folder: &mut F,
+    statements: Vec<ASTStatement>,
+) -> Vec<ASTStatement> {
+    statements
+        .into_iter()
+        .map(|statement| folder.fold_statement(statement))
+        .collect()
+}
+
+/// Folds a function body, copying it first only if other nodes still share it.
+fn fold_shared_statements<F: Fold + ?Sized>(
+    folder: &mut F,
+    statements: Rc<Vec<ASTStatement>>,
+) -> Rc<Vec<ASTStatement>> {
+    Rc::new(fold_statements(folder, Rc::unwrap_or_clone(statements)))
+}
+
+fn fold_expressions<F: Fold + ?Sized>(
+    folder: &mut F,
+    expressions: Vec<ASTExpression>,
+) -> Vec<ASTExpression> {
+    expressions
+        .into_iter()
+        .map(|expression| folder.fold_expression(expression))
+        .collect()
+}
+
+fn fold_parameters<F: Fold + ?Sized>(folder: &mut F, parameters: Vec<Parameter>) -> Vec<Parameter> {
+    parameters
+        .into_iter()
+        .map(|parameter| folder.fold_parameter(parameter))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::parser::{ast::BinaryOperator, lib::parse_source};
+
+    /// Replaces arithmetic on two number literals with its result, innermost first.
+    struct ConstantFolder {
+        folded: usize,
+    }
+
+    impl Fold for ConstantFolder {
+        fn fold_expression(&mut self, expression: ASTExpression) -> ASTExpression {
+            let expression = walk_expression(self, expression);
+
+            let ASTExpressionKind::BinaryExpression(binary) = &expression.kind else {
+                return expression;
+            };
+
+            let (ASTExpressionKind::NumericLiteral(left), ASTExpressionKind::NumericLiteral(right)) =
+                (&binary.left.kind, &binary.right.kind)
+            else {
+                return expression;
+            };
+
+            let value = match binary.operator {
+                BinaryOperator::Add => left + right,
+                BinaryOperator::Subtract => left - right,
+                BinaryOperator::Multiply => left * right,
+                _ => return expression,
+            };
+
+            self.folded += 1;
+
+            ASTExpression {
+                kind: ASTExpressionKind::NumericLiteral(value),
+                span: expression.span,
+            }
+        }
+    }
+
+    fn fold(ast: AST) -> (AST, usize) {
+        let mut folder = ConstantFolder { folded: 0 };
+        let ast = folder.fold_program(ast);
+
+        (ast, folder.folded)
+    }
+
+    fn expression_statement(ast: &AST) -> &ASTExpressionKind {
+        match &ast.statements[0].kind {
+            ASTStatementKind::ExpressionStatement(expression) => &expression.kind,
+            kind => panic!("expected an expression statement, got {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn folds_constant_arithmetic() {
+        let (ast, folded) = fold(parse_source("2 * 3"));
+
+        assert_eq!(folded, 1);
+        assert_eq!(
+            expression_statement(&ast),
+            &ASTExpressionKind::NumericLiteral(6.0)
+        );
+    }
+
+    #[test]
+    fn folds_nested_expressions_innermost_first() {
+        let (ast, folded) = fold(parse_source("f(1 + 2 * 3)"));
+
+        assert_eq!(folded, 2);
+
+        let ASTExpressionKind::CallExpression(call) = expression_statement(&ast) else {
+            panic!("expected a call");
+        };
+
+        assert_eq!(
+            call.arguments[0].kind,
+            ASTExpressionKind::NumericLiteral(7.0)
+        );
+    }
+
+    #[test]
+    fn folds_inside_branches_defaults_members_and_interpolations() {
+        let source = "
+            fn f(n = 2 * 3) { return a[1 + 1] + \"${2 * 2}\"; }
+            if x { 1 + 1; } else if 2 * 3 { y; } else { 3 - 1; }
+        ";
+
+        let (ast, folded) = fold(parse_source(source));
+
+        assert_eq!(folded, 6);
+
+        // nothing is left to fold, so every constant expression was reached
+        let (_, folded_again) = fold(ast);
+
+        assert_eq!(folded_again, 0);
+    }
+}
@@ -1,28 +1,108 @@
+use std::rc::Rc;
+
 use crate::{
+    diagnostics::diagnostic::Diagnostic,
     frontend::lexer::lib::{Token, Type, Value},
     frontend::{
-        lexer::lib::{tokenize, Object, Property},
+        lexer::error::LexError,
+        lexer::lib::tokenize,
         parser::ast::{
-            BinaryExpression, CallExpression, MemberExpression, VariableAssignment,
-            VariableDeclaration,
+            BinaryExpression, CallExpression, LogicalExpression, MemberExpression, MemberProperty,
+            UnaryExpression, VariableAssignment, VariableDeclaration,
         },
+        span::Span,
     },
 };
 
-use super::ast::{
-    ASTExpression, ASTExpressionBody, ASTExpressionKind, ASTStatement, ASTStatementKind,
-    ConditionalStatement, FunctionDeclaration, LoopStatement, AST,
+use super::{
+    ast::{
+        ASTExpression, ASTExpressionKind, ASTStatement, ASTStatementKind, Alternate,
+        BreakStatement, ConditionalStatement, ContinueStatement, ForStatement, FunctionDeclaration,
+        FunctionExpression, InterpolationPart, Parameter, Property, ReturnStatement,
+        WhileStatement, AST,
+    },
+    error::{ParseError, ParseErrorKind, ParseResult},
+    precedence::{
+        infix_operator, postfix_operator, prefix_operator, Associativity, InfixKind, PostfixKind,
+        Precedence,
+    },
 };
 
 pub struct Parser {
     pub tokens: Vec<Token>,
     pub cursor: usize,
+    errors: Vec<ParseError>,
+    warnings: Vec<Diagnostic>,
+    function_depth: usize,
+    /// How many `{ ... }` blocks enclose the statement being parsed, so error
+    /// recovery can tell a `}` closing one of them from a stray one.
+    block_depth: usize,
+    /// Labels of the loops enclosing the statement being parsed, `None` for
+    /// unlabeled loops. Function bodies start with an empty list.
+    loop_labels: Vec<Option<String>>,
 }
 
 impl Parser {
-    pub fn new(file_contents: String) -> Self {
-        let tokens = tokenize(&file_contents).unwrap();
-        Self { tokens, cursor: 0 }
+    pub fn new(file_contents: String) -> Result<Self, LexError> {
+        let tokens = tokenize(&file_contents)?;
+        Ok(Self {
+            tokens,
+            cursor: 0,
+            errors: vec![],
+            warnings: vec![],
+            function_depth: 0,
+            block_depth: 0,
+            loop_labels: vec![],
+        })
+    }
+
+    /// Problems that do not stop the program from running.
+    pub fn warnings(&self) -> &[Diagnostic] {
+        &self.warnings
+    }
+
+    /// Doc comments document the declaration right after them. Anywhere else they
+    /// are dropped with a warning, so they never break the code around them.
+    fn drop_unattached_doc_comments(&mut self) {
+        let mut tokens = std::mem::take(&mut self.tokens).into_iter().peekable();
+        let mut kept: Vec<Token> = vec![];
+        let mut doc_comments: Vec<Token> = vec![];
+
+        while let Some(token) = tokens.next() {
+            if token.r#type == Type::DocComment {
+                doc_comments.push(token);
+                continue;
+            }
+
+            if let (Some(first), Some(last)) = (doc_comments.first(), doc_comments.last()) {
+                let is_attached = match token.r#type {
+                    Type::Let | Type::Const => true,
+                    // `fn (` starts an anonymous function expression, not a declaration
+                    Type::Fn => tokens
+                        .peek()
+                        .is_some_and(|next| next.r#type != Type::OpenParen),
+                    _ => false,
+                };
+
+                if is_attached {
+                    kept.append(&mut doc_comments);
+                } else {
+                    self.warnings.push(
+                        Diagnostic::warning("doc comment is not attached to a declaration")
+                            .with_span(Some(first.span.to(last.span)))
+                            .with_label("ignored doc comment")
+                            .with_help(
+                                "doc comments document the `let`, `const` or `fn` right after them, use `//` for other comments",
+                            ),
+                    );
+                    doc_comments.clear();
+                }
+            }
+
+            kept.push(token);
+        }
+
+        self.tokens = kept;
     }
 
     fn not_eof(&self) -> bool {
@@ -36,301 +116,691 @@ impl Parser {
     fn advance(&mut self) -> Token {
         let token = self.tokens[self.cursor].clone();
 
-        self.cursor += 1;
+        if token.r#type != Type::EOF {
+            self.cursor += 1;
+        }
 
         token
     }
 
-    fn expect(&mut self, expected_type: Type) -> Token {
-        let token = self.advance();
+    /// Span of the last consumed token.
+    fn previous_span(&self) -> Span {
+        self.tokens[self.cursor.saturating_sub(1)].span
+    }
+
+    /// Span from `start` up to the end of the last consumed token.
+    fn span_from(&self, start: Span) -> Span {
+        start.to(self.previous_span())
+    }
+
+    /// Consumes the next token if it has the expected type, the token is left
+    /// in place otherwise so error recovery can decide what to skip.
+    fn expect(&mut self, expected_type: Type) -> ParseResult<Token> {
+        let token = self.peek();
 
         if token.r#type != expected_type {
-            panic!("expected {:?}, found {:?}", expected_type, token,);
+            return Err(self.unexpected_token(expected_type.describe()));
         }
 
-        token
+        Ok(self.advance())
+    }
+
+    fn expect_identifier(&mut self) -> ParseResult<String> {
+        let token = self.peek();
+
+        match (&token.r#type, &token.value) {
+            (Type::Identifier, Value::String(value)) => {
+                self.advance();
+                Ok(value.clone())
+            }
+            _ => Err(ParseError::new(
+                ParseErrorKind::ExpectedIdentifier,
+                format!("expected identifier, found {}", describe_token(&token)),
+                token.span,
+            )),
+        }
+    }
+
+    fn expect_label(&mut self) -> ParseResult<String> {
+        let token = self.peek();
+
+        match (&token.r#type, &token.value) {
+            (Type::Label, Value::String(name)) => {
+                self.advance();
+                Ok(name.clone())
+            }
+            _ => Err(self.unexpected_token("a loop label")),
+        }
+    }
+
+    fn unexpected_token(&self, expected: &str) -> ParseError {
+        let token = &self.tokens[self.cursor];
+
+        ParseError::new(
+            ParseErrorKind::UnexpectedToken,
+            format!("expected {}, found {}", expected, describe_token(token)),
+            token.span,
+        )
+    }
+
+    /// Skips tokens until a point where a new statement can start: right after a
+    /// `;`, before a statement keyword that begins a line or follows a brace, or
+    /// before a `}` closing an enclosing block. Braces opened by the malformed
+    /// statement since `start` are skipped together with their closing `}`.
+    fn synchronize(&mut self, start: usize) {
+        let mut open_braces = self.tokens[start..self.cursor]
+            .iter()
+            .fold(0isize, |open, token| match token.r#type {
+                Type::OpenBrace => open + 1,
+                Type::CloseBrace => open - 1,
+                _ => open,
+            })
+            .max(0);
+
+        while self.not_eof() {
+            let previous = &self.tokens[self.cursor.saturating_sub(1)];
+
+            if self.cursor > 0 && previous.r#type == Type::Semi {
+                return;
+            }
+
+            let token = self.peek();
+            let starts_statement = self.cursor == 0
+                || token.span.start.line > previous.span.end.line
+                || matches!(previous.r#type, Type::OpenBrace | Type::CloseBrace);
+
+            match token.r#type {
+                Type::Let
+                | Type::Const
+                | Type::Fn
+                | Type::For
+                | Type::While
+                | Type::If
+                | Type::Return
+                | Type::Break
+                | Type::Continue
+                | Type::Label
+                | Type::DocComment
+                    if starts_statement =>
+                {
+                    return
+                }
+                Type::CloseBrace if open_braces == 0 && self.block_depth > 0 => return,
+                Type::OpenBrace => open_braces += 1,
+                Type::CloseBrace => open_braces = (open_braces - 1).max(0),
+                _ => (),
+            }
+
+            self.advance();
+        }
     }
 
-    pub fn parse(&mut self) -> AST<'static> {
+    /// Parses a single statement, recording the error and skipping to the next
+    /// statement boundary when it is malformed.
+    fn parse_statement_recovering(&mut self) -> Option<ASTStatement> {
+        let start = self.cursor;
+
+        match self.parse_statement() {
+            Ok(statement) => Some(statement),
+            Err(error) => {
+                self.errors.push(error);
+
+                // always make progress, even when the statement failed on its first token
+                if self.cursor == start {
+                    self.advance();
+                }
+
+                self.synchronize(start);
+                None
+            }
+        }
+    }
+
+    pub fn parse(&mut self) -> Result<AST, Vec<ParseError>> {
         let mut statements: Vec<ASTStatement> = vec![];
 
+        self.drop_unattached_doc_comments();
+
         while self.not_eof() {
-            statements.push(self.parse_statement());
+            if let Some(statement) = self.parse_statement_recovering() {
+                statements.push(statement);
+            }
         }
 
-        AST {
-            kind: "Program",
-            statements,
+        if !self.errors.is_empty() {
+            return Err(std::mem::take(&mut self.errors));
         }
+
+        Ok(AST { statements })
     }
 
-    fn parse_statement(&mut self) -> ASTStatement {
+    fn parse_statement(&mut self) -> ParseResult<ASTStatement> {
+        let doc = self.parse_doc_comments();
+
         match self.peek().r#type {
-            Type::Let | Type::Const => self.parse_variable_declaration(),
-            Type::For => self.parse_for_statement(),
-            Type::While => self.parse_while_statement(),
-            Type::Fn => self.parse_function_declaration(),
+            Type::Let | Type::Const => self.parse_variable_declaration(doc),
+            // `fn (` starts an anonymous function expression instead
+            Type::Fn if self.tokens[self.cursor + 1].r#type != Type::OpenParen => {
+                self.parse_function_declaration(doc)
+            }
+            Type::For => self.parse_for_statement(None),
+            Type::While => self.parse_while_statement(None),
+            Type::Label => self.parse_labeled_loop(),
+            Type::Break | Type::Continue => self.parse_loop_control_statement(),
             Type::If => self.parse_conditional_statement(),
-            _ => ASTStatement {
-                kind: ASTStatementKind::ExpressionStatement(self.parse_expression()),
-            },
+            Type::Return => self.parse_return_statement(),
+            _ => {
+                let expression = self.parse_expression()?;
+
+                // expression statements may optionally end with a semicolon
+                if self.peek().r#type == Type::Semi {
+                    self.advance();
+                }
+
+                Ok(ASTStatement {
+                    span: expression.span,
+                    kind: ASTStatementKind::ExpressionStatement(expression),
+                })
+            }
         }
     }
 
-    fn parse_conditional_statement(&mut self) -> ASTStatement {
-        self.advance();
+    /// Collects consecutive `///` comments, only the ones right before a function or
+    /// variable declaration are left in the token list.
+    fn parse_doc_comments(&mut self) -> Option<String> {
+        let mut lines: Vec<String> = vec![];
 
-        let condition = Some(self.parse_expression());
+        while self.peek().r#type == Type::DocComment {
+            if let Value::String(line) = self.advance().value {
+                lines.push(line);
+            }
+        }
 
-        self.expect(Type::OpenBrace);
+        match lines.is_empty() {
+            true => None,
+            false => Some(lines.join("\n")),
+        }
+    }
 
-        let mut body: Vec<ASTStatement> = vec![self.parse_statement()];
+    /// Parses `{ statements }`, recovering from errors inside the block.
+    fn parse_block(&mut self) -> ParseResult<Vec<ASTStatement>> {
+        self.expect(Type::OpenBrace)?;
 
-        while self.peek().r#type != Type::EOF && self.peek().r#type != Type::CloseBrace {
-            body.push(self.parse_statement());
+        let mut body: Vec<ASTStatement> = vec![];
+
+        self.block_depth += 1;
+
+        while self.not_eof() && self.peek().r#type != Type::CloseBrace {
+            if let Some(statement) = self.parse_statement_recovering() {
+                body.push(statement);
+            }
         }
 
-        self.expect(Type::CloseBrace);
+        self.block_depth -= 1;
 
-        let mut last_statement = ASTStatement {
-            kind: ASTStatementKind::ConditionalStatement(ConditionalStatement {
-                condition,
-                consequence: body,
-                alternate: None,
-            }),
-        };
+        self.expect(Type::CloseBrace)?;
 
-        while self.peek().r#type == Type::Else {
-            self.advance();
+        Ok(body)
+    }
 
-            if self.peek().r#type == Type::If {
-                let next_statement = self.parse_conditional_statement();
+    fn parse_conditional_statement(&mut self) -> ParseResult<ASTStatement> {
+        let start = self.peek().span;
+        let conditional = self.parse_conditional()?;
 
-                last_statement.kind =
-                    ASTStatementKind::ConditionalStatement(ConditionalStatement {
-                        alternate: Some(Box::new(next_statement)),
-                        ..last_statement.kind.extract_conditional().unwrap().clone()
-                    });
-            } else {
-                self.expect(Type::OpenBrace);
+        Ok(ASTStatement {
+            kind: ASTStatementKind::ConditionalStatement(conditional),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses `if condition { ... }` followed by any number of `else if` branches
+    /// and an optional final `else`.
+    fn parse_conditional(&mut self) -> ParseResult<ConditionalStatement> {
+        self.advance(); // consume "if"
 
-                let mut alternate_body: Vec<ASTStatement> = vec![self.parse_statement()];
+        let condition = self.parse_expression()?;
+        let consequence = self.parse_block()?;
 
-                while self.peek().r#type != Type::EOF && self.peek().r#type != Type::CloseBrace {
-                    alternate_body.push(self.parse_statement());
+        let alternate = match self.peek().r#type {
+            Type::Else => {
+                self.advance(); // consume "else"
+
+                match self.peek().r#type {
+                    Type::If => Some(Alternate::ElseIf(Box::new(self.parse_conditional()?))),
+                    _ => Some(Alternate::Else(self.parse_block()?)),
                 }
+            }
+            _ => None,
+        };
 
-                self.expect(Type::CloseBrace);
+        Ok(ConditionalStatement {
+            condition,
+            consequence,
+            alternate,
+        })
+    }
 
-                let else_statement = ASTStatement {
-                    kind: ASTStatementKind::ConditionalStatement(ConditionalStatement {
-                        condition: None, // Represents an 'else' block
-                        consequence: alternate_body,
-                        alternate: None,
-                    }),
-                };
+    fn parse_return_statement(&mut self) -> ParseResult<ASTStatement> {
+        let start = self.advance().span; // consume "return"
 
-                last_statement.kind =
-                    ASTStatementKind::ConditionalStatement(ConditionalStatement {
-                        alternate: Some(Box::new(else_statement)),
-                        ..last_statement.kind.extract_conditional().unwrap().clone()
-                    });
-                break;
-            }
+        if self.function_depth == 0 {
+            return Err(ParseError::new(
+                ParseErrorKind::ReturnOutsideFunction,
+                "\"return\" can only be used inside a function",
+                start,
+            ));
+        }
+
+        let value = match self.peek().r#type {
+            Type::Semi | Type::CloseBrace | Type::EOF => None,
+            _ => Some(self.parse_expression()?),
+        };
+
+        if self.peek().r#type == Type::Semi {
+            self.advance();
         }
 
-        last_statement
+        Ok(ASTStatement {
+            kind: ASTStatementKind::ReturnStatement(ReturnStatement { value }),
+            span: self.span_from(start),
+        })
     }
 
-    fn parse_for_statement(&mut self) -> ASTStatement {
-        self.advance(); // consume "for"
+    /// Parses `'label: for ...` and `'label: while ...`.
+    fn parse_labeled_loop(&mut self) -> ParseResult<ASTStatement> {
+        let start = self.peek().span;
+        let name = self.expect_label()?;
+
+        self.expect(Type::Colon)?;
+
+        let mut statement = match self.peek().r#type {
+            Type::For => self.parse_for_statement(Some(name))?,
+            Type::While => self.parse_while_statement(Some(name))?,
+            _ => return Err(self.unexpected_token("a loop after the label")),
+        };
+
+        statement.span = start.to(statement.span);
 
-        let iterator_identifier = match self.expect(Type::Identifier).value {
-            Value::String(value) => value,
-            _ => panic!("expected identifier"),
-        }; // consume iterator identifier
+        Ok(statement)
+    }
 
-        self.expect(Type::In); // consume "in"
+    /// Parses a loop body, making its label a valid target for `break` and `continue`.
+    fn parse_loop_body(&mut self, label: &Option<String>) -> ParseResult<Vec<ASTStatement>> {
+        self.loop_labels.push(label.clone());
+        let body = self.parse_block();
+        self.loop_labels.pop();
 
-        let iterable = self.parse_expression(); // consume iterable
+        body
+    }
 
-        self.expect(Type::OpenBrace); // consume "{"
+    fn parse_loop_control_statement(&mut self) -> ParseResult<ASTStatement> {
+        let keyword = self.advance();
+        let start = keyword.span;
 
-        let mut body: Vec<ASTStatement> = vec![self.parse_statement()];
+        let label = match self.peek().r#type {
+            Type::Label => Some(self.expect_label()?),
+            _ => None,
+        };
 
-        while self.peek().r#type != Type::EOF && self.peek().r#type != Type::CloseBrace {
-            body.push(self.parse_statement());
+        if self.loop_labels.is_empty() {
+            return Err(ParseError::new(
+                ParseErrorKind::LoopControlOutsideLoop,
+                format!(
+                    "{} can only be used inside a loop",
+                    keyword.r#type.describe()
+                ),
+                self.span_from(start),
+            ));
         }
 
-        self.expect(Type::CloseBrace); // consume "}"
+        if let Some(name) = &label {
+            if !self.loop_labels.contains(&label) {
+                return Err(ParseError::new(
+                    ParseErrorKind::UndefinedLabel,
+                    format!("undefined loop label '{}", name),
+                    self.previous_span(),
+                ));
+            }
+        }
 
-        ASTStatement {
-            kind: ASTStatementKind::LoopStatement(LoopStatement {
-                identifier: Some(iterator_identifier),
-                condition: None,
-                interval: Some(iterable),
-                body,
-            }),
+        if self.peek().r#type == Type::Semi {
+            self.advance();
         }
+
+        let kind = match keyword.r#type {
+            Type::Break => ASTStatementKind::BreakStatement(BreakStatement { label }),
+            _ => ASTStatementKind::ContinueStatement(ContinueStatement { label }),
+        };
+
+        Ok(ASTStatement {
+            kind,
+            span: self.span_from(start),
+        })
     }
 
-    fn parse_while_statement(&mut self) -> ASTStatement {
-        self.advance(); // consume "while"
+    fn parse_for_statement(&mut self, label: Option<String>) -> ParseResult<ASTStatement> {
+        let start = self.advance().span; // consume "for"
 
-        let condition = Some(self.parse_expression()); // consume condition
+        let iterator_identifier = self.expect_identifier()?; // consume iterator identifier
 
-        self.expect(Type::OpenBrace); // consume "{"
+        self.expect(Type::In)?; // consume "in"
 
-        let mut body: Vec<ASTStatement> = vec![self.parse_statement()];
+        let iterable = self.parse_expression()?; // consume iterable
 
-        while self.peek().r#type != Type::EOF && self.peek().r#type != Type::CloseBrace {
-            body.push(self.parse_statement());
-        }
+        let body = self.parse_loop_body(&label)?;
 
-        self.expect(Type::CloseBrace); // consume "}"
+        Ok(ASTStatement {
+            kind: ASTStatementKind::ForStatement(ForStatement {
+                label,
+                identifier: iterator_identifier,
+                iterable,
+                body,
+            }),
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_while_statement(&mut self, label: Option<String>) -> ParseResult<ASTStatement> {
+        let start = self.advance().span; // consume "while"
+
+        let condition = self.parse_expression()?; // consume condition
 
-        ASTStatement {
-            kind: ASTStatementKind::LoopStatement(LoopStatement {
-                identifier: None,
+        let body = self.parse_loop_body(&label)?;
+
+        Ok(ASTStatement {
+            kind: ASTStatementKind::WhileStatement(WhileStatement {
+                label,
                 condition,
-                interval: None,
                 body,
             }),
-        }
+            span: self.span_from(start),
+        })
     }
 
-    fn parse_function_declaration(&mut self) -> ASTStatement {
-        self.advance(); // consume "fn"
+    fn parse_function_declaration(&mut self, doc: Option<String>) -> ParseResult<ASTStatement> {
+        let start = self.advance().span; // consume "fn"
 
-        let identifier = match self.expect(Type::Identifier).value {
-            Value::String(value) => value,
-            _ => panic!("expected identifier"),
-        };
+        let identifier = self.expect_identifier()?;
 
-        let args = self.parse_arguments();
+        let parameters = self.parse_parameters()?;
 
-        let mut parameters: Vec<String> = vec![];
+        let body = self.parse_function_body()?;
 
-        for arg in args {
-            if arg.kind != ASTExpressionKind::Identifier {
-                panic!("expected identifier");
+        Ok(ASTStatement {
+            kind: ASTStatementKind::FunctionDeclaration(FunctionDeclaration {
+                parameters,
+                identifier,
+                body,
+                doc,
+            }),
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses `(a, b = default, ...rest)`. Required parameters cannot follow optional
+    /// ones and the rest parameter, if any, comes last.
+    fn parse_parameters(&mut self) -> ParseResult<Vec<Parameter>> {
+        self.expect(Type::OpenParen)?;
+
+        let mut parameters: Vec<Parameter> = vec![];
+
+        while self.not_eof() && self.peek().r#type != Type::CloseParen {
+            let start = self.peek().span;
+            let rest = self.peek().r#type == Type::Ellipsis;
+
+            if rest {
+                self.advance();
             }
 
-            let arg_value = match arg.body {
-                ASTExpressionBody::Value(Value::String(value)) => value,
-                _ => panic!("expected identifier"),
+            let name = self.expect_identifier()?;
+
+            let default = match self.peek().r#type {
+                Type::Equals => {
+                    self.advance();
+                    Some(self.parse_expression()?)
+                }
+                _ => None,
             };
 
-            parameters.push(arg_value);
-        }
+            let previous = parameters.last();
 
-        self.expect(Type::OpenBrace);
+            let message = if previous.is_some_and(|previous| previous.rest) {
+                Some("the rest parameter must be the last parameter")
+            } else if rest && default.is_some() {
+                Some("a rest parameter cannot have a default value")
+            } else if !rest && default.is_none() && previous.is_some_and(|p| p.default.is_some()) {
+                Some("a required parameter cannot follow a parameter with a default value")
+            } else {
+                None
+            };
 
-        let mut body: Vec<ASTStatement> = vec![];
+            // the parameter list itself is well formed, so keep parsing the function
+            if let Some(message) = message {
+                self.errors.push(ParseError::new(
+                    ParseErrorKind::InvalidParameter,
+                    message,
+                    self.span_from(start),
+                ));
+            }
+
+            parameters.push(Parameter {
+                name,
+                default,
+                rest,
+            });
 
-        while self.peek().r#type != Type::EOF && self.peek().r#type != Type::CloseBrace {
-            body.push(self.parse_statement());
+            if self.peek().r#type != Type::CloseParen {
+                self.expect(Type::Comma)?;
+            }
         }
 
-        self.expect(Type::CloseBrace);
+        self.expect(Type::CloseParen)?;
 
-        ASTStatement {
-            kind: ASTStatementKind::FunctionDeclaration(FunctionDeclaration {
-                parameters,
-                identifier,
-                body,
-            }),
-        }
+        Ok(parameters)
     }
 
-    fn parse_variable_declaration(&mut self) -> ASTStatement {
-        let is_constant = self.advance().r#type == Type::Const;
+    fn parse_function_body(&mut self) -> ParseResult<Rc<Vec<ASTStatement>>> {
+        // `return` is only valid while at least one function body is being parsed,
+        // and loops outside the function cannot be targeted from inside it
+        let enclosing_loops = std::mem::take(&mut self.loop_labels);
+        self.function_depth += 1;
+        let body = self.parse_block();
+        self.function_depth -= 1;
+        self.loop_labels = enclosing_loops;
 
-        let identifier = self.advance();
-        let identifier = match identifier.r#type {
-            Type::Identifier => identifier.value,
-            _ => panic!("expected identifier"),
-        };
+        body.map(Rc::new)
+    }
+
+    fn parse_variable_declaration(&mut self, doc: Option<String>) -> ParseResult<ASTStatement> {
+        let keyword = self.advance();
+        let start = keyword.span;
+        let is_constant = keyword.r#type == Type::Const;
+
+        let identifier = self.expect_identifier()?;
 
         if self.peek().r#type == Type::Semi {
             self.advance();
             match is_constant {
-                true => panic!("Constants need to be declared with a value, no value provided"),
+                true => {
+                    return Err(ParseError::new(
+                        ParseErrorKind::MissingConstantValue,
+                        "Constants need to be declared with a value, no value provided",
+                        self.span_from(start),
+                    ))
+                }
                 false => {
-                    return ASTStatement {
+                    return Ok(ASTStatement {
                         kind: ASTStatementKind::VariableDeclaration(VariableDeclaration {
                             constant: false,
                             identifier,
                             value: None,
+                            doc,
                         }),
-                    }
+                        span: self.span_from(start),
+                    })
                 }
             }
         }
 
-        match self.advance().r#type {
-            Type::Equals => (),
-            _ => panic!("expected \"=\" at variable declaration"),
-        };
+        self.expect(Type::Equals)?;
+
+        let value = Some(self.parse_expression()?);
+
+        self.expect(Type::Semi)?;
 
-        let declaration = ASTStatement {
+        Ok(ASTStatement {
             kind: ASTStatementKind::VariableDeclaration(VariableDeclaration {
                 constant: is_constant,
                 identifier,
-                value: Some(self.parse_expression()),
+                value,
+                doc,
             }),
-        };
-
-        match self.advance().r#type {
-            Type::Semi => (),
-            _ => panic!(
-                "expected \";\" at variable declaration, found {:?}",
-                self.peek()
-            ),
-        };
-
-        declaration
+            span: self.span_from(start),
+        })
     }
 
-    fn parse_expression(&mut self) -> ASTExpression {
-        self.parse_assignment_expression()
+    fn parse_expression(&mut self) -> ParseResult<ASTExpression> {
+        self.parse_expression_with(Precedence::Lowest)
     }
 
-    fn parse_assignment_expression(&mut self) -> ASTExpression {
-        let left = self.parse_object_expression(); //TODO: switch this out with object_expression
+    /// Parses a prefix expression and keeps extending it with the postfix and infix
+    /// operators that follow, as long as they bind tighter than `min`. Operators and
+    /// their precedence and associativity are listed in `precedence.rs`.
+    fn parse_expression_with(&mut self, min: Precedence) -> ParseResult<ASTExpression> {
+        let mut left = self.parse_prefix_expression()?;
 
-        if self.peek().r#type == Type::Equals {
-            self.advance();
+        loop {
+            let token = self.peek();
 
-            let value = self.parse_assignment_expression();
+            // postfix operators bind tighter than anything else
+            if let Some(postfix) = postfix_operator(&token) {
+                left = self.parse_postfix_expression(left, postfix)?;
+                continue;
+            }
 
-            return ASTExpression {
-                kind: ASTExpressionKind::AssignmentExpression,
-                body: ASTExpressionBody::AssignmentExpressionBody(VariableAssignment {
-                    assignee: Box::new(left),
-                    value: Box::new(value),
-                }),
+            let Some(operator) = infix_operator(&token) else {
+                break;
             };
+
+            if !operator.binds_after(min) {
+                break;
+            }
+
+            self.advance();
+
+            let right = self.parse_expression_with(operator.precedence)?;
+            left = infix_expression(left, operator.kind, right)?;
+
+            let next = self.peek();
+            let is_chained = infix_operator(&next).is_some_and(|next_operator| {
+                next_operator.precedence == operator.precedence
+                    && operator.associativity == Associativity::None
+            });
+
+            if is_chained {
+                return Err(ParseError::new(
+                    ParseErrorKind::UnexpectedToken,
+                    format!(
+                        "{} cannot be chained, add parentheses to group it",
+                        describe_token(&next)
+                    ),
+                    next.span,
+                ));
+            }
         }
 
-        left
+        Ok(left)
+    }
+
+    /// Parses the prefix operators `-`, `+`, `!` and `~`, which can be stacked as
+    /// in `!-x`, or a primary expression.
+    fn parse_prefix_expression(&mut self) -> ParseResult<ASTExpression> {
+        let Some(operator) = prefix_operator(&self.peek()) else {
+            return self.parse_primary_expression();
+        };
+
+        let start = self.advance().span;
+        let argument = self.parse_expression_with(Precedence::Prefix)?;
+
+        Ok(ASTExpression {
+            span: start.to(argument.span),
+            kind: ASTExpressionKind::UnaryExpression(UnaryExpression {
+                operator,
+                argument: Box::new(argument),
+            }),
+        })
+    }
+
+    /// Parses a call `f(args)`, a member access `a.b` or an index `a[expr]` applied to `operand`.
+    fn parse_postfix_expression(
+        &mut self,
+        operand: ASTExpression,
+        postfix: PostfixKind,
+    ) -> ParseResult<ASTExpression> {
+        let start = operand.span;
+
+        let kind = match postfix {
+            PostfixKind::Call => ASTExpressionKind::CallExpression(CallExpression {
+                arguments: self.parse_arguments()?,
+                caller: Box::new(operand),
+            }),
+            PostfixKind::Member => {
+                self.advance(); // consume "."
+
+                ASTExpressionKind::MemberExpression(MemberExpression {
+                    object: Box::new(operand),
+                    property: MemberProperty::Named(self.expect_identifier()?),
+                })
+            }
+            PostfixKind::ComputedMember => {
+                self.advance(); // consume "["
+
+                let property = self.parse_expression()?;
+                self.expect(Type::CloseBracket)?;
+
+                ASTExpressionKind::MemberExpression(MemberExpression {
+                    object: Box::new(operand),
+                    property: MemberProperty::Computed(Box::new(property)),
+                })
+            }
+        };
+
+        Ok(ASTExpression {
+            kind,
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_arguments(&mut self) -> ParseResult<Vec<ASTExpression>> {
+        self.expect(Type::OpenParen)?;
+
+        let arguments = match self.peek().r#type {
+            Type::CloseParen => vec![],
+            _ => self.parse_arguments_list()?,
+        };
+
+        self.expect(Type::CloseParen)?;
+
+        Ok(arguments)
     }
 
-    fn parse_object_expression(&mut self) -> ASTExpression {
-        if self.peek().r#type != Type::OpenBrace {
-            return self.parse_comparative_expression();
+    fn parse_arguments_list(&mut self) -> ParseResult<Vec<ASTExpression>> {
+        let mut arguments = vec![self.parse_expression()?];
+
+        while self.peek().r#type == Type::Comma && self.not_eof() {
+            self.advance();
+            arguments.push(self.parse_expression()?);
         }
 
-        self.advance();
+        Ok(arguments)
+    }
+
+    fn parse_object_expression(&mut self) -> ParseResult<ASTExpression> {
+        let start = self.advance().span; // consume "{"
         let mut properties: Vec<Property> = vec![];
 
         while self.not_eof() && self.peek().r#type != Type::CloseBrace {
-            let key = self.expect(Type::Identifier);
-
-            let key = match key.value {
-                Value::String(value) => value,
-                _ => panic!("expected string"),
-            };
+            let key = self.expect_identifier()?;
 
             match self.peek().r#type {
                 Type::Comma => {
@@ -345,12 +815,9 @@ impl Parser {
                 _ => (),
             }
 
-            match self.advance().r#type {
-                Type::Colon => (),
-                _ => panic!("expected colon"),
-            }
+            self.expect(Type::Colon)?;
 
-            let value = self.parse_expression();
+            let value = self.parse_expression()?;
 
             properties.push(Property {
                 key,
@@ -358,227 +825,514 @@ impl Parser {
             });
 
             if self.peek().r#type != Type::CloseBrace {
-                self.expect(Type::Comma);
+                self.expect(Type::Comma)?;
             }
         }
 
-        self.expect(Type::CloseBrace);
+        self.expect(Type::CloseBrace)?;
 
-        ASTExpression {
-            kind: ASTExpressionKind::ObjectLiteral,
-            body: ASTExpressionBody::Value(Value::Object(Object { properties })),
-        }
+        Ok(ASTExpression {
+            kind: ASTExpressionKind::ObjectLiteral(properties),
+            span: self.span_from(start),
+        })
     }
 
-    //TODO: implement this
-    fn parse_comparative_expression(&mut self) -> ASTExpression {
-        let mut left = self.parse_interval_expression();
+    fn parse_primary_expression(&mut self) -> ParseResult<ASTExpression> {
+        let token = self.peek();
 
-        while self.peek().value == Value::String("==".to_string())
-            || self.peek().value == Value::String("!=".to_string())
-            || self.peek().value == Value::String("<".to_string())
-            || self.peek().value == Value::String("<=".to_string())
-            || self.peek().value == Value::String(">".to_string())
-            || self.peek().value == Value::String(">=".to_string())
-        {
-            let operator = self.advance().value;
+        let kind = match (token.r#type, token.value) {
+            (Type::Identifier, Value::String(name)) => ASTExpressionKind::Identifier(name),
+            (Type::OpenParen, _) if self.is_arrow_function() => return self.parse_arrow_function(),
+            (Type::Fn, _) => return self.parse_function_expression(),
+            (Type::OpenParen, _) => {
+                self.advance();
+                let value = self.parse_expression()?;
+                self.expect(Type::CloseParen)?;
 
-            let right = self.parse_interval_expression();
+                return Ok(value);
+            }
+            (Type::Number, Value::Number(value)) => ASTExpressionKind::NumericLiteral(value),
+            (Type::String, Value::String(value)) => ASTExpressionKind::StringLiteral(value),
+            (Type::OpenBrace, _) => return self.parse_object_expression(),
+            (Type::TemplateHead, _) => return self.parse_interpolated_string(),
+            (Type::OpenBracket, _) => return self.parse_array_expression(),
+            _ => return Err(self.unexpected_token("expression")),
+        };
 
-            left = ASTExpression {
-                kind: ASTExpressionKind::BinaryExpression,
-                body: ASTExpressionBody::BinaryExpressionBody(BinaryExpression {
-                    left: Box::new(left),
-                    operator,
-                    right: Box::new(right),
-                }),
-            };
-        }
+        self.advance();
 
-        left
+        Ok(ASTExpression {
+            kind,
+            span: token.span,
+        })
     }
 
-    fn parse_interval_expression(&mut self) -> ASTExpression {
-        let left = self.parse_additive_expression();
+    /// Checks if the `(` at the cursor starts the parameter list of an arrow function,
+    /// that is if its matching `)` is followed by `=>`. The scan for the `)` only runs
+    /// when the group starts like a parameter list, so nested parenthesized
+    /// expressions such as `((((a))))` are not scanned once per level.
+    fn is_arrow_function(&self) -> bool {
+        let starts_like_parameters = match self.tokens[self.cursor + 1].r#type {
+            Type::CloseParen | Type::Ellipsis => true,
+            Type::Identifier => matches!(
+                self.tokens[self.cursor + 2].r#type,
+                Type::Comma | Type::Equals | Type::CloseParen
+            ),
+            _ => false,
+        };
 
-        if self.peek().value == Value::String("..".to_string())
-            || self.peek().value == Value::String("..=".to_string())
-        {
-            let operator = self.advance().value;
-            let right = self.parse_additive_expression();
+        if !starts_like_parameters {
+            return false;
+        }
 
-            return ASTExpression {
-                kind: ASTExpressionKind::BinaryExpression,
-                body: ASTExpressionBody::BinaryExpressionBody(BinaryExpression {
-                    left: Box::new(left),
-                    operator,
-                    right: Box::new(right),
-                }),
-            };
+        let mut depth = 0;
+
+        for (index, token) in self.tokens.iter().enumerate().skip(self.cursor) {
+            match token.r#type {
+                Type::OpenParen => depth += 1,
+                Type::CloseParen => {
+                    depth -= 1;
+
+                    if depth == 0 {
+                        return self.tokens[index + 1].r#type == Type::Arrow;
+                    }
+                }
+                Type::EOF => return false,
+                _ => (),
+            }
         }
 
-        left
+        false
     }
 
-    fn parse_additive_expression(&mut self) -> ASTExpression {
-        let mut left = self.parse_multiplicative_expression();
+    /// Parses `fn (params) { body }`.
+    fn parse_function_expression(&mut self) -> ParseResult<ASTExpression> {
+        let start = self.advance().span; // consume "fn"
 
-        while self.peek().value == Value::String("+".to_string())
-            || self.peek().value == Value::String("-".to_string())
-        {
-            let operator = self.advance().value;
+        let parameters = self.parse_parameters()?;
+        let body = self.parse_function_body()?;
 
-            let right = self.parse_multiplicative_expression();
+        Ok(ASTExpression {
+            kind: ASTExpressionKind::FunctionExpression(FunctionExpression { parameters, body }),
+            span: self.span_from(start),
+        })
+    }
 
-            left = ASTExpression {
-                kind: ASTExpressionKind::BinaryExpression,
-                body: ASTExpressionBody::BinaryExpressionBody(BinaryExpression {
-                    left: Box::new(left),
-                    operator,
-                    right: Box::new(right),
-                }),
-            };
+    /// Parses `(params) => expression` and `(params) => { body }`.
+    fn parse_arrow_function(&mut self) -> ParseResult<ASTExpression> {
+        let start = self.peek().span;
+
+        let parameters = self.parse_parameters()?;
+        self.expect(Type::Arrow)?;
+
+        let body = match self.peek().r#type {
+            Type::OpenBrace => self.parse_function_body()?,
+            _ => {
+                let expression = self.parse_expression()?;
+
+                Rc::new(vec![ASTStatement {
+                    span: expression.span,
+                    kind: ASTStatementKind::ExpressionStatement(expression),
+                }])
+            }
+        };
+
+        Ok(ASTExpression {
+            kind: ASTExpressionKind::FunctionExpression(FunctionExpression { parameters, body }),
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_array_expression(&mut self) -> ParseResult<ASTExpression> {
+        let start = self.advance().span; // consume "["
+        let mut elements: Vec<ASTExpression> = vec![];
+
+        while self.not_eof() && self.peek().r#type != Type::CloseBracket {
+            elements.push(self.parse_expression()?);
+
+            if self.peek().r#type != Type::CloseBracket {
+                self.expect(Type::Comma)?;
+            }
         }
 
-        left
+        self.expect(Type::CloseBracket)?;
+
+        Ok(ASTExpression {
+            kind: ASTExpressionKind::ArrayLiteral(elements),
+            span: self.span_from(start),
+        })
     }
 
-    fn parse_multiplicative_expression(&mut self) -> ASTExpression {
-        let mut left = self.parse_call_member_expression();
+    fn parse_interpolated_string(&mut self) -> ParseResult<ASTExpression> {
+        let start = self.peek().span;
+        let mut parts: Vec<InterpolationPart> = vec![];
 
-        while self.peek().value == Value::String("*".to_string())
-            || self.peek().value == Value::String("/".to_string())
-            || self.peek().value == Value::String("%".to_string())
-        {
-            let operator = self.advance().value;
+        loop {
+            // the head, middle and tail tokens all carry the literal text around the expressions
+            let literal = self.advance();
 
-            let right = self.parse_primary_expression();
+            if let Value::String(text) = literal.value {
+                if !text.is_empty() {
+                    parts.push(InterpolationPart::Literal(text));
+                }
+            }
 
-            left = ASTExpression {
-                kind: ASTExpressionKind::BinaryExpression,
-                body: ASTExpressionBody::BinaryExpressionBody(BinaryExpression {
-                    left: Box::new(left),
-                    operator,
-                    right: Box::new(right),
-                }),
-            };
+            if literal.r#type == Type::TemplateTail {
+                break;
+            }
+
+            parts.push(InterpolationPart::Expression(self.parse_expression()?));
+
+            if !matches!(
+                self.peek().r#type,
+                Type::TemplateMiddle | Type::TemplateTail
+            ) {
+                return Err(self.unexpected_token("\"}\" to close the interpolation"));
+            }
         }
 
-        left
+        Ok(ASTExpression {
+            kind: ASTExpressionKind::InterpolatedString(parts),
+            span: self.span_from(start),
+        })
     }
+}
 
-    fn parse_call_member_expression(&mut self) -> ASTExpression {
-        let member = self.parse_member_expression();
+/// Builds the node for `left operator right`. Assignments are the only place that
+/// checks its operands, since only variables and members can be assigned to.
+fn infix_expression(
+    left: ASTExpression,
+    kind: InfixKind,
+    right: ASTExpression,
+) -> ParseResult<ASTExpression> {
+    let span = left.span.to(right.span);
+    let (left, right) = (Box::new(left), Box::new(right));
+
+    let kind = match kind {
+        InfixKind::Assignment => {
+            if !matches!(
+                left.kind,
+                ASTExpressionKind::Identifier(_) | ASTExpressionKind::MemberExpression(_)
+            ) {
+                return Err(ParseError::new(
+                    ParseErrorKind::InvalidAssignmentTarget,
+                    "Invalid assignment target, expected a variable, an object property or an array element",
+                    span,
+                ));
+            }
 
-        if self.peek().r#type == Type::OpenParen {
-            return self.parse_call_expression(member);
+            ASTExpressionKind::AssignmentExpression(VariableAssignment {
+                assignee: left,
+                value: right,
+            })
         }
+        InfixKind::Logical(operator) => ASTExpressionKind::LogicalExpression(LogicalExpression {
+            left,
+            operator,
+            right,
+        }),
+        InfixKind::Binary(operator) => ASTExpressionKind::BinaryExpression(BinaryExpression {
+            left,
+            operator,
+            right,
+        }),
+    };
+
+    Ok(ASTExpression { kind, span })
+}
 
-        member
+fn describe_token(token: &Token) -> String {
+    match (&token.r#type, &token.value) {
+        (Type::Identifier, Value::String(value)) => format!("identifier \"{}\"", value),
+        (Type::String, Value::String(value)) => format!("string \"{}\"", value),
+        (Type::Number, Value::Number(value)) => format!("number {}", value),
+        _ => token.r#type.describe().to_string(),
     }
+}
 
-    fn parse_call_expression(&mut self, caller: ASTExpression) -> ASTExpression {
-        let mut call_expression = ASTExpression {
-            kind: ASTExpressionKind::CallExpression,
-            body: ASTExpressionBody::CallExpressionBody(CallExpression {
-                arguments: self.parse_arguments(),
-                caller: Box::new(caller),
-            }),
-        };
+/// Parses `source` for a test, failing the test on any lex or parse error.
+#[cfg(test)]
+pub(crate) fn parse_source(source: &str) -> AST {
+    Parser::new(source.to_string())
+        .expect("source should lex")
+        .parse()
+        .expect("source should parse")
+}
 
-        if self.peek().r#type == Type::OpenParen {
-            call_expression = self.parse_call_expression(call_expression);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::parser::ast::LogicalOperator;
+
+    fn parse_errors(source: &str) -> Vec<ParseError> {
+        Parser::new(source.to_string())
+            .expect("source should lex")
+            .parse()
+            .expect_err("source should not parse")
+    }
+
+    /// Parses a single expression statement and prints it with every operation
+    /// parenthesized, so the tests can spell out how operators grouped.
+    fn grouping(source: &str) -> String {
+        let ast = parse_source(source);
+
+        match &ast.statements[..] {
+            [ASTStatement {
+                kind: ASTStatementKind::ExpressionStatement(expression),
+                ..
+            }] => render(expression),
+            statements => panic!("expected one expression statement, got {:?}", statements),
         }
+    }
+
+    fn render(expression: &ASTExpression) -> String {
+        match &expression.kind {
+            ASTExpressionKind::Identifier(name) => name.clone(),
+            ASTExpressionKind::NumericLiteral(value) => value.to_string(),
+            ASTExpressionKind::AssignmentExpression(assignment) => format!(
+                "({} = {})",
+                render(&assignment.assignee),
+                render(&assignment.value)
+            ),
+            ASTExpressionKind::BinaryExpression(binary) => format!(
+                "({} {} {})",
+                render(&binary.left),
+                binary.operator,
+                render(&binary.right)
+            ),
+            ASTExpressionKind::LogicalExpression(logical) => {
+                let operator = match logical.operator {
+                    LogicalOperator::And => "&&",
+                    LogicalOperator::Or => "||",
+                };
+
+                format!(
+                    "({} {} {})",
+                    render(&logical.left),
+                    operator,
+                    render(&logical.right)
+                )
+            }
+            ASTExpressionKind::UnaryExpression(unary) => {
+                format!("({}{})", unary.operator, render(&unary.argument))
+            }
+            ASTExpressionKind::CallExpression(call) => {
+                let arguments: Vec<String> = call.arguments.iter().map(render).collect();
 
-        call_expression
+                format!("{}({})", render(&call.caller), arguments.join(", "))
+            }
+            ASTExpressionKind::MemberExpression(member) => match &member.property {
+                MemberProperty::Named(name) => format!("{}.{}", render(&member.object), name),
+                MemberProperty::Computed(property) => {
+                    format!("{}[{}]", render(&member.object), render(property))
+                }
+            },
+            kind => panic!("unexpected expression {:?}", kind),
+        }
     }
 
-    fn parse_arguments(&mut self) -> Vec<ASTExpression> {
-        self.expect(Type::OpenParen);
+    fn first_error(source: &str) -> ParseError {
+        parse_errors(source)
+            .into_iter()
+            .next()
+            .expect("at least one error")
+    }
 
-        let arguments = match self.peek().r#type {
-            Type::CloseParen => vec![],
-            _ => self.parse_arguments_list(),
-        };
+    /// Every error reported for `source` as `(line, column, message)`.
+    fn errors(source: &str) -> Vec<(usize, usize, String)> {
+        parse_errors(source)
+            .into_iter()
+            .map(|error| {
+                (
+                    error.span.start.line,
+                    error.span.start.column,
+                    error.message,
+                )
+            })
+            .collect()
+    }
 
-        self.expect(Type::CloseParen);
+    fn error(line: usize, column: usize, message: &str) -> (usize, usize, String) {
+        (line, column, message.to_string())
+    }
 
-        arguments
+    #[test]
+    fn reports_every_error_in_a_file() {
+        let source = "let a = ;\nlet b = 1 +;\nfn f(x { }\nlet c = 2;\nlet d = c *;";
+
+        assert_eq!(
+            errors(source),
+            [
+                error(1, 9, "expected expression, found \";\""),
+                error(2, 12, "expected expression, found \";\""),
+                error(3, 8, "expected \",\", found \"{\""),
+                error(5, 12, "expected expression, found \";\""),
+            ]
+        );
     }
 
-    fn parse_arguments_list(&mut self) -> Vec<ASTExpression> {
-        let mut arguments = vec![self.parse_assignment_expression()];
+    #[test]
+    fn recovers_inside_blocks_and_keeps_parsing_after_them() {
+        let source = "fn f() {\n  let a = ;\n  g(;\n}\nwhile x { let b = 1 + ; }\nlet c = ;";
+
+        assert_eq!(
+            errors(source),
+            [
+                error(2, 11, "expected expression, found \";\""),
+                error(3, 5, "expected expression, found \";\""),
+                error(5, 23, "expected expression, found \";\""),
+                error(6, 9, "expected expression, found \";\""),
+            ]
+        );
+    }
 
-        while self.peek().r#type == Type::Comma && self.not_eof() {
-            self.advance();
-            arguments.push(self.parse_assignment_expression());
-        }
+    #[test]
+    fn skips_braces_of_the_malformed_statement() {
+        assert_eq!(
+            errors("let a = {x: 1, y 2};\nlet b = ;"),
+            [
+                error(1, 18, "expected \":\", found number 2"),
+                error(2, 9, "expected expression, found \";\""),
+            ]
+        );
+        assert_eq!(
+            errors("fn g( { }\nlet b = ;"),
+            [
+                error(1, 7, "expected identifier, found \"{\""),
+                error(2, 9, "expected expression, found \";\""),
+            ]
+        );
+        assert_eq!(
+            errors("let x = if true { 1 } else { 2 };"),
+            [error(1, 9, "expected expression, found \"if\"")]
+        );
+    }
 
-        arguments
+    #[test]
+    fn keeps_parsing_functions_with_misordered_parameters() {
+        assert_eq!(
+            errors("fn f(a = 1, b) { return a +; }\nlet g = (...r, s = 2) => r;"),
+            [
+                error(
+                    1,
+                    13,
+                    "a required parameter cannot follow a parameter with a default value"
+                ),
+                error(1, 28, "expected expression, found \";\""),
+                error(2, 16, "the rest parameter must be the last parameter"),
+            ]
+        );
+        assert_eq!(
+            first_error("fn f(...r = []) {}").kind,
+            ParseErrorKind::InvalidParameter
+        );
     }
 
-    fn parse_member_expression(&mut self) -> ASTExpression {
-        let mut object = self.parse_primary_expression();
+    #[test]
+    fn reports_a_stray_closing_brace_once() {
+        assert_eq!(
+            errors("}\nlet a = 1;\nlet b = ;"),
+            [
+                error(1, 1, "expected expression, found \"}\""),
+                error(3, 9, "expected expression, found \";\""),
+            ]
+        );
+    }
 
-        while self.peek().r#type == Type::Dot || self.peek().r#type == Type::OpenBracket {
-            let operator = self.advance();
-            let property: ASTExpression;
-            let computed: bool;
+    #[test]
+    fn exponent_binds_tighter_than_prefix_minus() {
+        assert_eq!(grouping("-2 ** 2"), "(-(2 ** 2))");
+        assert_eq!(grouping("2 ** -1"), "(2 ** (-1))");
+    }
 
-            match operator.r#type {
-                Type::Dot => {
-                    property = self.parse_primary_expression();
-                    computed = false;
+    #[test]
+    fn exponent_is_right_associative() {
+        assert_eq!(grouping("2 ** 3 ** 2"), "(2 ** (3 ** 2))");
+    }
 
-                    if property.kind != ASTExpressionKind::Identifier {
-                        panic!("expected identifier");
-                    }
-                }
+    #[test]
+    fn arithmetic_is_left_associative() {
+        assert_eq!(grouping("a - b - c"), "((a - b) - c)");
+        assert_eq!(grouping("a / b * c"), "((a / b) * c)");
+        assert_eq!(grouping("a + b * c"), "(a + (b * c))");
+    }
 
-                _ => {
-                    computed = true;
-                    property = self.parse_expression();
+    #[test]
+    fn assignment_is_right_associative() {
+        assert_eq!(grouping("a = b = c"), "(a = (b = c))");
+        assert_eq!(grouping("a.b = c || d"), "(a.b = (c || d))");
+    }
 
-                    self.expect(Type::CloseBracket);
-                }
-            }
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(grouping("a || b && c"), "(a || (b && c))");
+        assert_eq!(grouping("a && b || c"), "((a && b) || c)");
+    }
 
-            object = ASTExpression {
-                kind: ASTExpressionKind::MemberExpression,
-                body: ASTExpressionBody::MemberExpressionBody(MemberExpression {
-                    object: Box::new(object),
-                    property: Box::new(property),
-                    computed,
-                }),
-            };
-        }
+    #[test]
+    fn comparisons_bind_tighter_than_logical_operators() {
+        assert_eq!(grouping("a < b && c == d"), "((a < b) && (c == d))");
+    }
 
-        object
+    #[test]
+    fn postfix_binds_tighter_than_prefix() {
+        assert_eq!(grouping("-f()"), "(-f())");
+        assert_eq!(grouping("!a.b[c]"), "(!a.b[c])");
+        assert_eq!(grouping("-a.b(c)"), "(-a.b(c))");
     }
 
-    fn parse_primary_expression(&mut self) -> ASTExpression {
-        let token = self.advance();
+    #[test]
+    fn tells_arrow_functions_from_parenthesized_groups() {
+        assert_eq!(grouping("(a) + (b * c)"), "(a + (b * c))");
+        assert_eq!(
+            grouping(&format!("{}a{}", "(".repeat(50), ")".repeat(50))),
+            "a"
+        );
+
+        for source in [
+            "() => 1",
+            "(a) => a",
+            "(a, b = 2) => a",
+            "(...rest) => rest",
+        ] {
+            let ast = parse_source(source);
+
+            assert!(
+                matches!(
+                    &ast.statements[0].kind,
+                    ASTStatementKind::ExpressionStatement(ASTExpression {
+                        kind: ASTExpressionKind::FunctionExpression(_),
+                        ..
+                    })
+                ),
+                "{}",
+                source
+            );
+        }
+    }
 
-        match token.r#type {
-            Type::Identifier => ASTExpression {
-                kind: ASTExpressionKind::Identifier,
-                body: ASTExpressionBody::Value(token.value),
-            },
-            Type::OpenParen => {
-                let value = self.parse_expression();
-                self.expect(Type::CloseParen);
+    #[test]
+    fn ranges_cannot_be_chained() {
+        let error = first_error("a..b..c");
+
+        assert_eq!(error.kind, ParseErrorKind::UnexpectedToken);
+        assert!(
+            error.message.contains("cannot be chained"),
+            "{}",
+            error.message
+        );
+        assert_eq!(grouping("a + 1..b"), "((a + 1) .. b)");
+    }
 
-                value
-            }
-            Type::Number => ASTExpression {
-                kind: ASTExpressionKind::NumericLiteral,
-                body: ASTExpressionBody::Value(token.value),
-            },
-            Type::String => ASTExpression {
-                kind: ASTExpressionKind::StringLiteral,
-                body: ASTExpressionBody::Value(token.value),
-            },
-            _ => panic!("unexpected token found during parsing: {:?}", token),
+    #[test]
+    fn rejects_invalid_assignment_targets() {
+        for source in ["1 = 2", "a + b = c", "f() = 3"] {
+            assert_eq!(
+                first_error(source).kind,
+                ParseErrorKind::InvalidAssignmentTarget,
+                "{}",
+                source
+            );
         }
     }
 }
@@ -1,2 +1,13 @@
 pub(crate) mod ast;
+pub(crate) mod error;
 pub(crate) mod lib;
+pub(crate) mod precedence;
+
+// traversals for linters, rewriters and analyses, the interpreter itself does not
+// walk the AST through them yet
+#[allow(dead_code)]
+pub(crate) mod fold;
+#[allow(dead_code)]
+pub(crate) mod visit;
+#[allow(dead_code)]
+pub(crate) mod visit_mut;
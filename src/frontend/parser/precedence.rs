@@ -0,0 +1,159 @@
+use crate::frontend::lexer::lib::{Token, Type, Value};
+
+use super::ast::{BinaryOperator, LogicalOperator, UnaryOperator};
+
+/// How tightly operators bind, from loosest to tightest. An operator only takes
+/// operands made of operators that bind tighter than itself, so `1 + 2 * 3` groups
+/// as `1 + (2 * 3)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Precedence {
+    Lowest,
+    Assignment,
+    LogicalOr,
+    LogicalAnd,
+    Comparison,
+    Interval,
+    Additive,
+    Multiplicative,
+    Prefix,
+    Exponent,
+}
+
+/// How a chain of operators with the same precedence groups: `a - b - c` is
+/// `(a - b) - c`, `a = b = c` is `a = (b = c)` and `a..b..c` is an error.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Associativity {
+    Left,
+    Right,
+    None,
+}
+
+/// The expression node an infix operator produces.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InfixKind {
+    Assignment,
+    Logical(LogicalOperator),
+    Binary(BinaryOperator),
+}
+
+/// Operators written after their operand, which bind tighter than any other.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PostfixKind {
+    Call,
+    Member,
+    ComputedMember,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InfixOperator {
+    pub(crate) precedence: Precedence,
+    pub(crate) associativity: Associativity,
+    pub(crate) kind: InfixKind,
+}
+
+impl InfixOperator {
+    /// Whether this operator takes the expression parsed so far as its left operand
+    /// while parsing an operand that has to bind tighter than `min`. Operators of the
+    /// same precedence only nest to the right when they are right associative.
+    pub fn binds_after(&self, min: Precedence) -> bool {
+        self.precedence > min
+            || (self.precedence == min && self.associativity == Associativity::Right)
+    }
+}
+
+/// Every prefix operator parses its operand at `Precedence::Prefix`, so `-a * b`
+/// is `(-a) * b` while `-a ** b` is `-(a ** b)`.
+const PREFIX_OPERATORS: &[(&str, UnaryOperator)] = &[
+    ("!", UnaryOperator::Not),
+    ("~", UnaryOperator::BitwiseNot),
+    ("-", UnaryOperator::Negate),
+    ("+", UnaryOperator::Plus),
+];
+
+#[rustfmt::skip]
+const INFIX_OPERATORS: &[(&str, Precedence, Associativity, InfixKind)] = &[
+    ("=",   Precedence::Assignment,     Associativity::Right, InfixKind::Assignment),
+    ("||",  Precedence::LogicalOr,      Associativity::Left,  InfixKind::Logical(LogicalOperator::Or)),
+    ("&&",  Precedence::LogicalAnd,     Associativity::Left,  InfixKind::Logical(LogicalOperator::And)),
+    ("==",  Precedence::Comparison,     Associativity::Left,  InfixKind::Binary(BinaryOperator::Equal)),
+    ("!=",  Precedence::Comparison,     Associativity::Left,  InfixKind::Binary(BinaryOperator::NotEqual)),
+    ("<",   Precedence::Comparison,     Associativity::Left,  InfixKind::Binary(BinaryOperator::LessThan)),
+    ("<=",  Precedence::Comparison,     Associativity::Left,  InfixKind::Binary(BinaryOperator::LessEqual)),
+    (">",   Precedence::Comparison,     Associativity::Left,  InfixKind::Binary(BinaryOperator::GreaterThan)),
+    (">=",  Precedence::Comparison,     Associativity::Left,  InfixKind::Binary(BinaryOperator::GreaterEqual)),
+    ("..",  Precedence::Interval,       Associativity::None,  InfixKind::Binary(BinaryOperator::Range)),
+    ("..=", Precedence::Interval,       Associativity::None,  InfixKind::Binary(BinaryOperator::InclusiveRange)),
+    ("+",   Precedence::Additive,       Associativity::Left,  InfixKind::Binary(BinaryOperator::Add)),
+    ("-",   Precedence::Additive,       Associativity::Left,  InfixKind::Binary(BinaryOperator::Subtract)),
+    ("*",   Precedence::Multiplicative, Associativity::Left,  InfixKind::Binary(BinaryOperator::Multiply)),
+    ("/",   Precedence::Multiplicative, Associativity::Left,  InfixKind::Binary(BinaryOperator::Divide)),
+    ("%",   Precedence::Multiplicative, Associativity::Left,  InfixKind::Binary(BinaryOperator::Remainder)),
+    ("**",  Precedence::Exponent,       Associativity::Right, InfixKind::Binary(BinaryOperator::Exponent)),
+];
+
+const POSTFIX_OPERATORS: &[(&str, PostfixKind)] = &[
+    ("(", PostfixKind::Call),
+    (".", PostfixKind::Member),
+    ("[", PostfixKind::ComputedMember),
+];
+
+pub fn prefix_operator(token: &Token) -> Option<UnaryOperator> {
+    let text = operator_text(token)?;
+
+    PREFIX_OPERATORS
+        .iter()
+        .find(|(operator, _)| *operator == text)
+        .map(|&(_, operator)| operator)
+}
+
+pub fn infix_operator(token: &Token) -> Option<InfixOperator> {
+    let text = operator_text(token)?;
+
+    INFIX_OPERATORS
+        .iter()
+        .find(|(operator, ..)| *operator == text)
+        .map(|&(_, precedence, associativity, kind)| InfixOperator {
+            precedence,
+            associativity,
+            kind,
+        })
+}
+
+pub fn postfix_operator(token: &Token) -> Option<PostfixKind> {
+    let text = operator_text(token)?;
+
+    POSTFIX_OPERATORS
+        .iter()
+        .find(|(operator, _)| *operator == text)
+        .map(|&(_, kind)| kind)
+}
+
+/// The text of an operator token. Only punctuation tokens count as operators, so
+/// literals, labels and doc comments never do, even when their text looks like one.
+fn operator_text(token: &Token) -> Option<&str> {
+    let is_operator = matches!(
+        token.r#type,
+        Type::Operator
+            | Type::Equals
+            | Type::DoubleEquals
+            | Type::NotEqual
+            | Type::LessThan
+            | Type::LessEqual
+            | Type::GreaterThan
+            | Type::GreaterEqual
+            | Type::And
+            | Type::Or
+            | Type::Not
+            | Type::Tilde
+            | Type::Interval
+            | Type::InclusiveInterval
+            | Type::OpenParen
+            | Type::Dot
+            | Type::OpenBracket
+    );
+
+    match &token.value {
+        Value::String(value) if is_operator => Some(value),
+        _ => None,
+    }
+}
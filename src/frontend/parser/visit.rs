@@ -0,0 +1,361 @@
+//! Read-only traversal of the AST. Implement `Visitor`, override the methods for the
+//! nodes of interest and call the matching `walk_*` function from an override to keep
+//! descending into its children.
+
+use super::ast::{
+    ASTExpression, ASTExpressionKind, ASTStatement, ASTStatementKind, Alternate, BinaryExpression,
+    BreakStatement, CallExpression, ConditionalStatement, ContinueStatement, ForStatement,
+    FunctionDeclaration, FunctionExpression, InterpolationPart, LogicalExpression,
+    MemberExpression, MemberProperty, Parameter, Property, ReturnStatement, UnaryExpression,
+    VariableAssignment, VariableDeclaration, WhileStatement, AST,
+};
+
+pub trait Visitor {
+    fn visit_program(&mut self, program: &AST) {
+        walk_program(self, program);
+    }
+
+    fn visit_statement(&mut self, statement: &ASTStatement) {
+        walk_statement(self, statement);
+    }
+
+    fn visit_variable_declaration(&mut self, declaration: &VariableDeclaration) {
+        walk_variable_declaration(self, declaration);
+    }
+
+    fn visit_function_declaration(&mut self, declaration: &FunctionDeclaration) {
+        walk_function_declaration(self, declaration);
+    }
+
+    fn visit_parameter(&mut self, parameter: &Parameter) {
+        walk_parameter(self, parameter);
+    }
+
+    fn visit_conditional_statement(&mut self, conditional: &ConditionalStatement) {
+        walk_conditional_statement(self, conditional);
+    }
+
+    fn visit_for_statement(&mut self, for_statement: &ForStatement) {
+        walk_for_statement(self, for_statement);
+    }
+
+    fn visit_while_statement(&mut self, while_statement: &WhileStatement) {
+        walk_while_statement(self, while_statement);
+    }
+
+    fn visit_return_statement(&mut self, return_statement: &ReturnStatement) {
+        walk_return_statement(self, return_statement);
+    }
+
+    fn visit_break_statement(&mut self, _break_statement: &BreakStatement) {}
+
+    fn visit_continue_statement(&mut self, _continue_statement: &ContinueStatement) {}
+
+    /// Identifiers and number and string literals have no children, override this
+    /// method to inspect them.
+    fn visit_expression(&mut self, expression: &ASTExpression) {
+        walk_expression(self, expression);
+    }
+
+    fn visit_binary_expression(&mut self, binary: &BinaryExpression) {
+        walk_binary_expression(self, binary);
+    }
+
+    fn visit_logical_expression(&mut self, logical: &LogicalExpression) {
+        walk_logical_expression(self, logical);
+    }
+
+    fn visit_assignment_expression(&mut self, assignment: &VariableAssignment) {
+        walk_assignment_expression(self, assignment);
+    }
+
+    fn visit_unary_expression(&mut self, unary: &UnaryExpression) {
+        walk_unary_expression(self, unary);
+    }
+
+    fn visit_call_expression(&mut self, call: &CallExpression) {
+        walk_call_expression(self, call);
+    }
+
+    fn visit_member_expression(&mut self, member: &MemberExpression) {
+        walk_member_expression(self, member);
+    }
+
+    fn visit_function_expression(&mut self, function: &FunctionExpression) {
+        walk_function_expression(self, function);
+    }
+
+    fn visit_property(&mut self, property: &Property) {
+        walk_property(self, property);
+    }
+
+    fn visit_interpolation_part(&mut self, part: &InterpolationPart) {
+        walk_interpolation_part(self, part);
+    }
+}
+
+pub fn walk_program<V: Visitor + ?Sized>(visitor: &mut V, program: &AST) {
+    for statement in &program.statements {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &ASTStatement) {
+    match &statement.kind {
+        ASTStatementKind::ExpressionStatement(expression) => visitor.visit_expression(expression),
+        ASTStatementKind::VariableDeclaration(declaration) => {
+            visitor.visit_variable_declaration(declaration)
+        }
+        ASTStatementKind::FunctionDeclaration(declaration) => {
+            visitor.visit_function_declaration(declaration)
+        }
+        ASTStatementKind::ConditionalStatement(conditional) => {
+            visitor.visit_conditional_statement(conditional)
+        }
+        ASTStatementKind::ForStatement(for_statement) => visitor.visit_for_statement(for_statement),
+        ASTStatementKind::WhileStatement(while_statement) => {
+            visitor.visit_while_statement(while_statement)
+        }
+        ASTStatementKind::ReturnStatement(return_statement) => {
+            visitor.visit_return_statement(return_statement)
+        }
+        ASTStatementKind::BreakStatement(break_statement) => {
+            visitor.visit_break_statement(break_statement)
+        }
+        ASTStatementKind::ContinueStatement(continue_statement) => {
+            visitor.visit_continue_statement(continue_statement)
+        }
+    }
+}
+
+pub fn walk_variable_declaration<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    declaration: &VariableDeclaration,
+) {
+    if let Some(value) = &declaration.value {
+        visitor.visit_expression(value);
+    }
+}
+
+pub fn walk_function_declaration<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    declaration: &FunctionDeclaration,
+) {
+    for parameter in &declaration.parameters {
+        visitor.visit_parameter(parameter);
+    }
+
+    for statement in declaration.body.iter() {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_parameter<V: Visitor + ?Sized>(visitor: &mut V, parameter: &Parameter) {
+    if let Some(default) = &parameter.default {
+        visitor.visit_expression(default);
+    }
+}
+
+pub fn walk_conditional_statement<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    conditional: &ConditionalStatement,
+) {
+    visitor.visit_expression(&conditional.condition);
+
+    for statement in &conditional.consequence {
+        visitor.visit_statement(statement);
+    }
+
+    match &conditional.alternate {
+        Some(Alternate::ElseIf(conditional)) => visitor.visit_conditional_statement(conditional),
+        Some(Alternate::Else(block)) => {
+            for statement in block {
+                visitor.visit_statement(statement);
+            }
+        }
+        None => (),
+    }
+}
+
+pub fn walk_for_statement<V: Visitor + ?Sized>(visitor: &mut V, for_statement: &ForStatement) {
+    visitor.visit_expression(&for_statement.iterable);
+
+    for statement in &for_statement.body {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_while_statement<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    while_statement: &WhileStatement,
+) {
+    visitor.visit_expression(&while_statement.condition);
+
+    for statement in &while_statement.body {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_return_statement<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    return_statement: &ReturnStatement,
+) {
+    if let Some(value) = &return_statement.value {
+        visitor.visit_expression(value);
+    }
+}
+
+pub fn walk_expression<V: Visitor + ?Sized>(visitor: &mut V, expression: &ASTExpression) {
+    match &expression.kind {
+        ASTExpressionKind::Identifier(_)
+        | ASTExpressionKind::StringLiteral(_)
+        | ASTExpressionKind::NumericLiteral(_) => (),
+        ASTExpressionKind::AssignmentExpression(assignment) => {
+            visitor.visit_assignment_expression(assignment)
+        }
+        ASTExpressionKind::MemberExpression(member) => visitor.visit_member_expression(member),
+        ASTExpressionKind::CallExpression(call) => visitor.visit_call_expression(call),
+        ASTExpressionKind::BinaryExpression(binary) => visitor.visit_binary_expression(binary),
+        ASTExpressionKind::LogicalExpression(logical) => visitor.visit_logical_expression(logical),
+        ASTExpressionKind::UnaryExpression(unary) => visitor.visit_unary_expression(unary),
+        ASTExpressionKind::FunctionExpression(function) => {
+            visitor.visit_function_expression(function)
+        }
+        ASTExpressionKind::InterpolatedString(parts) => {
+            for part in parts {
+                visitor.visit_interpolation_part(part);
+            }
+        }
+        ASTExpressionKind::ObjectLiteral(properties) => {
+            for property in properties {
+                visitor.visit_property(property);
+            }
+        }
+        ASTExpressionKind::ArrayLiteral(elements) => {
+            for element in elements {
+                visitor.visit_expression(element);
+            }
+        }
+    }
+}
+
+pub fn walk_binary_expression<V: Visitor + ?Sized>(visitor: &mut V, binary: &BinaryExpression) {
+    visitor.visit_expression(&binary.left);
+    visitor.visit_expression(&binary.right);
+}
+
+pub fn walk_logical_expression<V: Visitor + ?Sized>(visitor: &mut V, logical: &LogicalExpression) {
+    visitor.visit_expression(&logical.left);
+    visitor.visit_expression(&logical.right);
+}
+
+pub fn walk_assignment_expression<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    assignment: &VariableAssignment,
+) {
+    visitor.visit_expression(&assignment.assignee);
+    visitor.visit_expression(&assignment.value);
+}
+
+pub fn walk_unary_expression<V: Visitor + ?Sized>(visitor: &mut V, unary: &UnaryExpression) {
+    visitor.visit_expression(&unary.argument);
+}
+
+pub fn walk_call_expression<V: Visitor + ?Sized>(visitor: &mut V, call: &CallExpression) {
+    visitor.visit_expression(&call.caller);
+
+    for argument in &call.arguments {
+        visitor.visit_expression(argument);
+    }
+}
+
+pub fn walk_member_expression<V: Visitor + ?Sized>(visitor: &mut V, member: &MemberExpression) {
+    visitor.visit_expression(&member.object);
+
+    if let MemberProperty::Computed(property) = &member.property {
+        visitor.visit_expression(property);
+    }
+}
+
+pub fn walk_function_expression<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    function: &FunctionExpression,
+) {
+    for parameter in &function.parameters {
+        visitor.visit_parameter(parameter);
+    }
+
+    for statement in function.body.iter() {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_property<V: Visitor + ?Sized>(visitor: &mut V, property: &Property) {
+    if let Some(value) = &property.value {
+        visitor.visit_expression(value);
+    }
+}
+
+pub fn walk_interpolation_part<V: Visitor + ?Sized>(visitor: &mut V, part: &InterpolationPart) {
+    if let InterpolationPart::Expression(expression) = part {
+        visitor.visit_expression(expression);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::parser::lib::parse_source;
+
+    struct IdentifierCollector {
+        names: Vec<String>,
+    }
+
+    impl Visitor for IdentifierCollector {
+        fn visit_expression(&mut self, expression: &ASTExpression) {
+            if let ASTExpressionKind::Identifier(name) = &expression.kind {
+                self.names.push(name.clone());
+            }
+
+            walk_expression(self, expression);
+        }
+    }
+
+    fn identifiers(source: &str) -> Vec<String> {
+        let ast = parse_source(source);
+        let mut collector = IdentifierCollector { names: Vec::new() };
+
+        collector.visit_program(&ast);
+
+        collector.names
+    }
+
+    #[test]
+    fn reaches_every_branch_of_else_if_chains() {
+        assert_eq!(
+            identifiers("if a { b; } else if c { d; } else if e { f; } else { g; }"),
+            ["a", "b", "c", "d", "e", "f", "g"]
+        );
+    }
+
+    #[test]
+    fn reaches_parameter_defaults() {
+        assert_eq!(
+            identifiers("fn f(x = a, y = b + 1) { return x; } let g = (z = c) => z;"),
+            ["a", "b", "x", "c", "z"]
+        );
+    }
+
+    #[test]
+    fn reaches_computed_members_but_not_named_ones() {
+        assert_eq!(identifiers("a.b[c].d[e + f];"), ["a", "c", "e", "f"]);
+    }
+
+    #[test]
+    fn reaches_interpolation_parts() {
+        assert_eq!(
+            identifiers("print(\"${a} and ${b[c]} in ${ {k: d}.k }\");"),
+            ["print", "a", "b", "c", "d"]
+        );
+    }
+}
@@ -0,0 +1,384 @@
+//! In-place traversal of the AST, the mutable counterpart of `visit.rs`. Implement
+//! `VisitorMut` to rewrite nodes where they are, for example to rename variables,
+//! and call the matching `walk_*` function from an override to keep descending.
+
+use std::rc::Rc;
+
+use super::ast::{
+    ASTExpression, ASTExpressionKind, ASTStatement, ASTStatementKind, Alternate, BinaryExpression,
+    BreakStatement, CallExpression, ConditionalStatement, ContinueStatement, ForStatement,
+    FunctionDeclaration, FunctionExpression, InterpolationPart, LogicalExpression,
+    MemberExpression, MemberProperty, Parameter, Property, ReturnStatement, UnaryExpression,
+    VariableAssignment, VariableDeclaration, WhileStatement, AST,
+};
+
+pub trait VisitorMut {
+    fn visit_program(&mut self, program: &mut AST) {
+        walk_program(self, program);
+    }
+
+    fn visit_statement(&mut self, statement: &mut ASTStatement) {
+        walk_statement(self, statement);
+    }
+
+    fn visit_variable_declaration(&mut self, declaration: &mut VariableDeclaration) {
+        walk_variable_declaration(self, declaration);
+    }
+
+    fn visit_function_declaration(&mut self, declaration: &mut FunctionDeclaration) {
+        walk_function_declaration(self, declaration);
+    }
+
+    fn visit_parameter(&mut self, parameter: &mut Parameter) {
+        walk_parameter(self, parameter);
+    }
+
+    fn visit_conditional_statement(&mut self, conditional: &mut ConditionalStatement) {
+        walk_conditional_statement(self, conditional);
+    }
+
+    fn visit_for_statement(&mut self, for_statement: &mut ForStatement) {
+        walk_for_statement(self, for_statement);
+    }
+
+    fn visit_while_statement(&mut self, while_statement: &mut WhileStatement) {
+        walk_while_statement(self, while_statement);
+    }
+
+    fn visit_return_statement(&mut self, return_statement: &mut ReturnStatement) {
+        walk_return_statement(self, return_statement);
+    }
+
+    fn visit_break_statement(&mut self, _break_statement: &mut BreakStatement) {}
+
+    fn visit_continue_statement(&mut self, _continue_statement: &mut ContinueStatement) {}
+
+    /// Identifiers and number and string literals have no children, override this
+    /// method to inspect them.
+    fn visit_expression(&mut self, expression: &mut ASTExpression) {
+        walk_expression(self, expression);
+    }
+
+    fn visit_binary_expression(&mut self, binary: &mut BinaryExpression) {
+        walk_binary_expression(self, binary);
+    }
+
+    fn visit_logical_expression(&mut self, logical: &mut LogicalExpression) {
+        walk_logical_expression(self, logical);
+    }
+
+    fn visit_assignment_expression(&mut self, assignment: &mut VariableAssignment) {
+        walk_assignment_expression(self, assignment);
+    }
+
+    fn visit_unary_expression(&mut self, unary: &mut UnaryExpression) {
+        walk_unary_expression(self, unary);
+    }
+
+    fn visit_call_expression(&mut self, call: &mut CallExpression) {
+        walk_call_expression(self, call);
+    }
+
+    fn visit_member_expression(&mut self, member: &mut MemberExpression) {
+        walk_member_expression(self, member);
+    }
+
+    fn visit_function_expression(&mut self, function: &mut FunctionExpression) {
+        walk_function_expression(self, function);
+    }
+
+    fn visit_property(&mut self, property: &mut Property) {
+        walk_property(self, property);
+    }
+
+    fn visit_interpolation_part(&mut self, part: &mut InterpolationPart) {
+        walk_interpolation_part(self, part);
+    }
+}
+
+pub fn walk_program<V: VisitorMut + ?Sized>(visitor: &mut V, program: &mut AST) {
+    for statement in &mut program.statements {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_statement<V: VisitorMut + ?Sized>(visitor: &mut V, statement: &mut ASTStatement) {
+    match &mut statement.kind {
+        ASTStatementKind::ExpressionStatement(expression) => visitor.visit_expression(expression),
+        ASTStatementKind::VariableDeclaration(declaration) => {
+            visitor.visit_variable_declaration(declaration)
+        }
+        ASTStatementKind::FunctionDeclaration(declaration) => {
+            visitor.visit_function_declaration(declaration)
+        }
+        ASTStatementKind::ConditionalStatement(conditional) => {
+            visitor.visit_conditional_statement(conditional)
+        }
+        ASTStatementKind::ForStatement(for_statement) => visitor.visit_for_statement(for_statement),
+        ASTStatementKind::WhileStatement(while_statement) => {
+            visitor.visit_while_statement(while_statement)
+        }
+        ASTStatementKind::ReturnStatement(return_statement) => {
+            visitor.visit_return_statement(return_statement)
+        }
+        ASTStatementKind::BreakStatement(break_statement) => {
+            visitor.visit_break_statement(break_statement)
+        }
+        ASTStatementKind::ContinueStatement(continue_statement) => {
+            visitor.visit_continue_statement(continue_statement)
+        }
+    }
+}
+
+pub fn walk_variable_declaration<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    declaration: &mut VariableDeclaration,
+) {
+    if let Some(value) = &mut declaration.value {
+        visitor.visit_expression(value);
+    }
+}
+
+pub fn walk_function_declaration<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    declaration: &mut FunctionDeclaration,
+) {
+    for parameter in &mut declaration.parameters {
+        visitor.visit_parameter(parameter);
+    }
+
+    for statement in Rc::make_mut(&mut declaration.body) {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_parameter<V: VisitorMut + ?Sized>(visitor: &mut V, parameter: &mut Parameter) {
+    if let Some(default) = &mut parameter.default {
+        visitor.visit_expression(default);
+    }
+}
+
+pub fn walk_conditional_statement<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    conditional: &mut ConditionalStatement,
+) {
+    visitor.visit_expression(&mut conditional.condition);
+
+    for statement in &mut conditional.consequence {
+        visitor.visit_statement(statement);
+    }
+
+    match &mut conditional.alternate {
+        Some(Alternate::ElseIf(conditional)) => visitor.visit_conditional_statement(conditional),
+        Some(Alternate::Else(block)) => {
+            for statement in block {
+                visitor.visit_statement(statement);
+            }
+        }
+        None => (),
+    }
+}
+
+pub fn walk_for_statement<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    for_statement: &mut ForStatement,
+) {
+    visitor.visit_expression(&mut for_statement.iterable);
+
+    for statement in &mut for_statement.body {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_while_statement<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    while_statement: &mut WhileStatement,
+) {
+    visitor.visit_expression(&mut while_statement.condition);
+
+    for statement in &mut while_statement.body {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_return_statement<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    return_statement: &mut ReturnStatement,
+) {
+    if let Some(value) = &mut return_statement.value {
+        visitor.visit_expression(value);
+    }
+}
+
+pub fn walk_expression<V: VisitorMut + ?Sized>(visitor: &mut V, expression: &mut ASTExpression) {
+    match &mut expression.kind {
+        ASTExpressionKind::Identifier(_)
+        | ASTExpressionKind::StringLiteral(_)
+        | ASTExpressionKind::NumericLiteral(_) => (),
+        ASTExpressionKind::AssignmentExpression(assignment) => {
+            visitor.visit_assignment_expression(assignment)
+        }
+        ASTExpressionKind::MemberExpression(member) => visitor.visit_member_expression(member),
+        ASTExpressionKind::CallExpression(call) => visitor.visit_call_expression(call),
+        ASTExpressionKind::BinaryExpression(binary) => visitor.visit_binary_expression(binary),
+        ASTExpressionKind::LogicalExpression(logical) => visitor.visit_logical_expression(logical),
+        ASTExpressionKind::UnaryExpression(unary) => visitor.visit_unary_expression(unary),
+        ASTExpressionKind::FunctionExpression(function) => {
+            visitor.visit_function_expression(function)
+        }
+        ASTExpressionKind::InterpolatedString(parts) => {
+            for part in parts {
+                visitor.visit_interpolation_part(part);
+            }
+        }
+        ASTExpressionKind::ObjectLiteral(properties) => {
+            for property in properties {
+                visitor.visit_property(property);
+            }
+        }
+        ASTExpressionKind::ArrayLiteral(elements) => {
+            for element in elements {
+                visitor.visit_expression(element);
+            }
+        }
+    }
+}
+
+pub fn walk_binary_expression<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    binary: &mut BinaryExpression,
+) {
+    visitor.visit_expression(&mut binary.left);
+    visitor.visit_expression(&mut binary.right);
+}
+
+pub fn walk_logical_expression<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    logical: &mut LogicalExpression,
+) {
+    visitor.visit_expression(&mut logical.left);
+    visitor.visit_expression(&mut logical.right);
+}
+
+pub fn walk_assignment_expression<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    assignment: &mut VariableAssignment,
+) {
+    visitor.visit_expression(&mut assignment.assignee);
+    visitor.visit_expression(&mut assignment.value);
+}
+
+pub fn walk_unary_expression<V: VisitorMut + ?Sized>(visitor: &mut V, unary: &mut UnaryExpression) {
+    visitor.visit_expression(&mut unary.argument);
+}
+
+pub fn walk_call_expression<V: VisitorMut + ?Sized>(visitor: &mut V, call: &mut CallExpression) {
+    visitor.visit_expression(&mut call.caller);
+
+    for argument in &mut call.arguments {
+        visitor.visit_expression(argument);
+    }
+}
+
+pub fn walk_member_expression<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    member: &mut MemberExpression,
+) {
+    visitor.visit_expression(&mut member.object);
+
+    if let MemberProperty::Computed(property) = &mut member.property {
+        visitor.visit_expression(property);
+    }
+}
+
+pub fn walk_function_expression<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    function: &mut FunctionExpression,
+) {
+    for parameter in &mut function.parameters {
+        visitor.visit_parameter(parameter);
+    }
+
+    for statement in Rc::make_mut(&mut function.body) {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_property<V: VisitorMut + ?Sized>(visitor: &mut V, property: &mut Property) {
+    if let Some(value) = &mut property.value {
+        visitor.visit_expression(value);
+    }
+}
+
+pub fn walk_interpolation_part<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    part: &mut InterpolationPart,
+) {
+    if let InterpolationPart::Expression(expression) = part {
+        visitor.visit_expression(expression);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::parser::lib::parse_source;
+
+    struct Rename<'a> {
+        from: &'a str,
+        to: &'a str,
+        renamed: usize,
+    }
+
+    impl VisitorMut for Rename<'_> {
+        fn visit_expression(&mut self, expression: &mut ASTExpression) {
+            if let ASTExpressionKind::Identifier(name) = &mut expression.kind {
+                if name == self.from {
+                    *name = self.to.to_string();
+                    self.renamed += 1;
+                }
+            }
+
+            walk_expression(self, expression);
+        }
+    }
+
+    fn rename(source: &str, from: &str, to: &str) -> (AST, usize) {
+        let mut ast = parse_source(source);
+        let mut rename = Rename {
+            from,
+            to,
+            renamed: 0,
+        };
+
+        rename.visit_program(&mut ast);
+
+        (ast, rename.renamed)
+    }
+
+    #[test]
+    fn renames_identifiers_in_place() {
+        let source = "
+            fn f(n = a) { return a[a] + \"${a}\"; }
+            if x { a; } else if a { y; } else { a = 1; }
+        ";
+        let expected = "
+            fn f(n = b) { return b[b] + \"${b}\"; }
+            if x { b; } else if b { y; } else { b = 1; }
+        ";
+
+        let (ast, renamed) = rename(source, "a", "b");
+
+        assert_eq!(renamed, 7);
+        // spans are unchanged since both names are one character long
+        assert_eq!(ast.statements, parse_source(expected).statements);
+    }
+
+    #[test]
+    fn leaves_named_members_alone() {
+        let (ast, renamed) = rename("a.a;", "a", "b");
+
+        assert_eq!(renamed, 1);
+        assert_eq!(ast.statements, parse_source("b.a;").statements);
+    }
+}
@@ -0,0 +1,12 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::runtime::values::{ArrayValue, RuntimeValue, ValueType, ValueTypes};
+
+pub fn build_array_runtime_value(elements: Vec<RuntimeValue>) -> RuntimeValue {
+    RuntimeValue {
+        value_type: ValueType::Array(ArrayValue {
+            r#type: ValueTypes::Array,
+            elements: Rc::new(RefCell::new(elements)),
+        }),
+    }
+}
@@ -1,16 +1,20 @@
 use crate::runtime::{
     environment::Environment,
-    values::{NativeFunctionValue, RuntimeValue, ValueType, ValueTypes},
+    error::RuntimeError,
+    values::{Arity, NativeFunctionValue, RuntimeValue, ValueType, ValueTypes},
 };
 
 pub fn build_native_function(
-    call: fn(Vec<RuntimeValue>, env: Environment) -> RuntimeValue,
+    name: &str,
+    arity: Arity,
+    call: fn(Vec<RuntimeValue>, env: Environment) -> Result<RuntimeValue, RuntimeError>,
 ) -> RuntimeValue {
     RuntimeValue {
         value_type: ValueType::NativeFunction(NativeFunctionValue {
             r#type: ValueTypes::Function,
+            name: String::from(name),
+            arity,
             call,
         }),
     }
 }
-
@@ -8,4 +8,3 @@ pub fn build_number_runtime_value(number: f64) -> RuntimeValue {
         }),
     }
 }
-
@@ -1,10 +1,10 @@
-use crate::runtime::values::{NullValue, RuntimeValue, ValueType, ValueTypes};
+use crate::runtime::values::{RuntimeValue, StringValue, ValueType, ValueTypes};
 
-pub fn build_null_runtime_value() -> RuntimeValue {
-    RuntimeVlaue {
-        value_type: ValueType::Null(NullValue {
-            r#type: ValueTypes::Null,
-            value: "null".to_string(),
+pub fn build_string_runtime_value(string: String) -> RuntimeValue {
+    RuntimeValue {
+        value_type: ValueType::String(StringValue {
+            r#type: ValueTypes::String,
+            value: string,
         }),
     }
 }
@@ -0,0 +1,67 @@
+use crate::runtime::{
+    error::{RuntimeError, RuntimeErrorKind},
+    values::{Arity, FunctionValue},
+};
+
+impl Arity {
+    pub fn exactly(count: usize) -> Self {
+        Self {
+            min: count,
+            max: Some(count),
+        }
+    }
+
+    pub fn at_least(count: usize) -> Self {
+        Self {
+            min: count,
+            max: None,
+        }
+    }
+
+    /// Fails with an arity mismatch when `function_name` is called with `found` arguments.
+    pub fn check(&self, function_name: &str, found: usize) -> Result<(), RuntimeError> {
+        let accepted = found >= self.min && self.max.is_none_or(|max| found <= max);
+
+        if accepted {
+            return Ok(());
+        }
+
+        let expected = match self.max {
+            Some(max) if max == self.min => count_arguments(max),
+            Some(max) => format!("{} to {}", self.min, count_arguments(max)),
+            None => format!("at least {}", count_arguments(self.min)),
+        };
+
+        Err(RuntimeError::new(
+            RuntimeErrorKind::ArityMismatch,
+            format!("{}() expects {}, found {}", function_name, expected, found),
+        ))
+    }
+}
+
+impl FunctionValue {
+    /// Parameters with a default value are optional and a rest parameter accepts any
+    /// number of arguments.
+    pub fn arity(&self) -> Arity {
+        let required = self
+            .parameters
+            .iter()
+            .filter(|parameter| parameter.default.is_none() && !parameter.rest)
+            .count();
+
+        match self.parameters.iter().any(|parameter| parameter.rest) {
+            true => Arity::at_least(required),
+            false => Arity {
+                min: required,
+                max: Some(self.parameters.len()),
+            },
+        }
+    }
+}
+
+fn count_arguments(count: usize) -> String {
+    match count {
+        1 => String::from("1 argument"),
+        count => format!("{} arguments", count),
+    }
+}
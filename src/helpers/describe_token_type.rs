@@ -0,0 +1,54 @@
+use crate::frontend::lexer::lib::Type;
+
+impl Type {
+    /// Human readable name of the token type, used in syntax errors.
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Type::Identifier => "identifier",
+            Type::Let => "\"let\"",
+            Type::Const => "\"const\"",
+            Type::Fn => "\"fn\"",
+            Type::For => "\"for\"",
+            Type::In => "\"in\"",
+            Type::If => "\"if\"",
+            Type::Else => "\"else\"",
+            Type::While => "\"while\"",
+            Type::Return => "\"return\"",
+            Type::Break => "\"break\"",
+            Type::Continue => "\"continue\"",
+            Type::Operator => "operator",
+            Type::Interval => "\"..\"",
+            Type::InclusiveInterval => "\"..=\"",
+            Type::GreaterThan => "\">\"",
+            Type::LessThan => "\"<\"",
+            Type::GreaterEqual => "\">=\"",
+            Type::LessEqual => "\"<=\"",
+            Type::Not => "\"!\"",
+            Type::Tilde => "\"~\"",
+            Type::NotEqual => "\"!=\"",
+            Type::DoubleEquals => "\"==\"",
+            Type::And => "\"&&\"",
+            Type::Or => "\"||\"",
+            Type::OpenParen => "\"(\"",
+            Type::CloseParen => "\")\"",
+            Type::Comma => "\",\"",
+            Type::Colon => "\":\"",
+            Type::OpenBrace => "\"{\"",
+            Type::CloseBrace => "\"}\"",
+            Type::OpenBracket => "\"[\"",
+            Type::CloseBracket => "\"]\"",
+            Type::Semi => "\";\"",
+            Type::Dot => "\".\"",
+            Type::Ellipsis => "\"...\"",
+            Type::Equals => "\"=\"",
+            Type::Arrow => "\"=>\"",
+            Type::Number => "number",
+            Type::String => "string",
+            Type::Label => "loop label",
+            Type::TemplateHead => "interpolated string",
+            Type::TemplateMiddle | Type::TemplateTail => "\"}\"",
+            Type::DocComment => "doc comment",
+            Type::EOF => "end of file",
+        }
+    }
+}
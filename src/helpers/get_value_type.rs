@@ -0,0 +1,16 @@
+use crate::runtime::values::{RuntimeValue, ValueType, ValueTypes};
+
+impl RuntimeValue {
+    pub fn get_value_type(&self) -> ValueTypes {
+        match &self.value_type {
+            ValueType::Null(value) => value.r#type.clone(),
+            ValueType::Number(value) => value.r#type.clone(),
+            ValueType::Boolean(value) => value.r#type.clone(),
+            ValueType::Object(value) => value.r#type.clone(),
+            ValueType::Array(value) => value.r#type.clone(),
+            ValueType::String(value) => value.r#type.clone(),
+            ValueType::NativeFunction(value) => value.r#type.clone(),
+            ValueType::Function(value) => value.r#type.clone(),
+        }
+    }
+}
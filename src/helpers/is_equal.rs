@@ -0,0 +1,97 @@
+use std::rc::Rc;
+
+use crate::runtime::values::{RuntimeValue, ValueType};
+
+impl RuntimeValue {
+    /// Equality rule used by `==` and `!=`. Values of different types are never equal.
+    /// Numbers follow IEEE 754 (so `NaN` is not equal to itself), strings, booleans and
+    /// `null` compare by value, objects and arrays are equal when they are the same value
+    /// or when they are deeply equal property by property (element by element for
+    /// arrays), and functions compare by identity. Two values that contain themselves
+    /// are equal when their cycles line up.
+    pub fn is_equal(&self, other: &RuntimeValue) -> bool {
+        self.is_equal_within(other, &mut vec![])
+    }
+
+    /// `comparing` holds the pairs of objects and arrays whose comparison is in progress,
+    /// reaching one of them again means a cycle, which is treated as equal so the
+    /// result is decided by the rest of the structure.
+    fn is_equal_within(
+        &self,
+        other: &RuntimeValue,
+        comparing: &mut Vec<(*const (), *const ())>,
+    ) -> bool {
+        match (&self.value_type, &other.value_type) {
+            (ValueType::Null(_), ValueType::Null(_)) => true,
+            (ValueType::Number(left), ValueType::Number(right)) => left.value == right.value,
+            (ValueType::Boolean(left), ValueType::Boolean(right)) => left.value == right.value,
+            (ValueType::String(left), ValueType::String(right)) => left.value == right.value,
+            (ValueType::Object(left), ValueType::Object(right)) => {
+                if Rc::ptr_eq(&left.properties, &right.properties) {
+                    return true;
+                }
+
+                let pair = (
+                    Rc::as_ptr(&left.properties) as *const (),
+                    Rc::as_ptr(&right.properties) as *const (),
+                );
+
+                if comparing.contains(&pair) {
+                    return true;
+                }
+
+                let left = left.properties.borrow();
+                let right = right.properties.borrow();
+
+                comparing.push(pair);
+
+                let equal = left.len() == right.len()
+                    && left.iter().all(|(key, value)| {
+                        right.get(key).is_some_and(|other_value| {
+                            value.is_equal_within(other_value, comparing)
+                        })
+                    });
+
+                comparing.pop();
+
+                equal
+            }
+            (ValueType::Array(left), ValueType::Array(right)) => {
+                if Rc::ptr_eq(&left.elements, &right.elements) {
+                    return true;
+                }
+
+                let pair = (
+                    Rc::as_ptr(&left.elements) as *const (),
+                    Rc::as_ptr(&right.elements) as *const (),
+                );
+
+                if comparing.contains(&pair) {
+                    return true;
+                }
+
+                let left = left.elements.borrow();
+                let right = right.elements.borrow();
+
+                comparing.push(pair);
+
+                let equal = left.len() == right.len()
+                    && left
+                        .iter()
+                        .zip(right.iter())
+                        .all(|(value, other_value)| value.is_equal_within(other_value, comparing));
+
+                comparing.pop();
+
+                equal
+            }
+            (ValueType::NativeFunction(left), ValueType::NativeFunction(right)) => {
+                std::ptr::fn_addr_eq(left.call, right.call)
+            }
+            (ValueType::Function(left), ValueType::Function(right)) => {
+                Rc::ptr_eq(&left.body, &right.body) && left.scope.ptr_eq(&right.scope)
+            }
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use crate::runtime::values::{RuntimeValue, ValueType};
+
+impl RuntimeValue {
+    /// Truthiness rule used by conditionals:
+    /// `null` and `false` are falsy, numbers are falsy when `0` or `NaN`,
+    /// strings are falsy when empty, objects, arrays and functions are always truthy.
+    pub fn is_truthy(&self) -> bool {
+        match &self.value_type {
+            ValueType::Null(_) => false,
+            ValueType::Boolean(value) => value.value,
+            ValueType::Number(value) => value.value != 0.0 && !value.value.is_nan(),
+            ValueType::String(value) => !value.value.is_empty(),
+            ValueType::Object(_) => true,
+            ValueType::Array(_) => true,
+            ValueType::NativeFunction(_) => true,
+            ValueType::Function(_) => true,
+        }
+    }
+}
@@ -1,7 +1,13 @@
+pub(crate) mod build_array_runtime_value;
 pub(crate) mod build_bool_runtime_value;
 pub(crate) mod build_native_function;
 pub(crate) mod build_null_runtime_value;
 pub(crate) mod build_number_runtime_value;
-pub(crate) mod extract_conditional;
-pub(crate) mod is_string;
+pub(crate) mod build_string_runtime_value;
+pub(crate) mod check_arity;
+pub(crate) mod describe_token_type;
+pub(crate) mod get_value_type;
+pub(crate) mod is_equal;
+pub(crate) mod is_truthy;
 pub(crate) mod read_file_contents;
+pub(crate) mod to_display_string;
@@ -1,21 +1,45 @@
-use std::env;
+use std::{
+    env,
+    io::{self, IsTerminal},
+};
 
-pub fn read_file_contents_from_args() -> (String, bool, bool) {
+pub struct ProgramArgs {
+    pub(crate) file_path: String,
+    pub(crate) file_contents: String,
+    pub(crate) should_print_ast: bool,
+    pub(crate) should_print_tokens: bool,
+    pub(crate) use_color: bool,
+}
+
+pub fn read_file_contents_from_args() -> ProgramArgs {
     let args: Vec<String> = env::args().collect();
-    let file_path = &args[1];
-    let mut should_print_ast = false;
-    let mut should_print_tokens = false;
+    let Some(file_path) = args.get(1).cloned() else {
+        eprintln!("usage: cog <file> [-ast] [-tokens] [-no-color]");
+        eprintln!("       cog explain <code>");
+        std::process::exit(1);
+    };
 
-    if args.contains(&"-ast".to_string()) {
-        should_print_ast = true;
-    }
+    let should_print_ast = args.contains(&"-ast".to_string());
+    let should_print_tokens = args.contains(&"-tokens".to_string());
 
-    if args.contains(&"-tokens".to_string()) {
-        should_print_tokens = true;
-    }
+    // colors are only used when writing diagnostics to a terminal
+    let use_color = !args.contains(&"-no-color".to_string())
+        && env::var_os("NO_COLOR").is_none()
+        && io::stderr().is_terminal();
 
-    let file_contents =
-        std::fs::read_to_string(file_path).expect("Something went wrong reading the file");
+    let file_contents = match std::fs::read_to_string(&file_path) {
+        Ok(file_contents) => file_contents,
+        Err(error) => {
+            eprintln!("cannot read {}: {}", file_path, error);
+            std::process::exit(1);
+        }
+    };
 
-    (file_contents, should_print_ast, should_print_tokens)
+    ProgramArgs {
+        file_path,
+        file_contents,
+        should_print_ast,
+        should_print_tokens,
+        use_color,
+    }
 }
@@ -0,0 +1,87 @@
+use std::rc::Rc;
+
+use crate::runtime::values::{RuntimeValue, ValueType};
+
+impl RuntimeValue {
+    /// Converts a value to the text `print` and interpolated strings show for it.
+    ///
+    /// Strings are written as is at the top level but quoted inside objects and arrays,
+    /// objects list their properties sorted by key and an object or array that
+    /// contains itself is shown as `{...}` or `[...]` where the cycle starts.
+    pub fn to_display_string(&self) -> String {
+        self.write_display_string(&mut vec![], false)
+    }
+
+    fn write_display_string(&self, ancestors: &mut Vec<*const ()>, nested: bool) -> String {
+        match &self.value_type {
+            ValueType::Null(_) => String::from("null"),
+            ValueType::Boolean(value) => value.value.to_string(),
+            ValueType::Number(value) => format_number(value.value),
+            ValueType::String(value) if nested => format!("{:?}", value.value),
+            ValueType::String(value) => value.value.clone(),
+            ValueType::NativeFunction(_) => String::from("<native fn>"),
+            ValueType::Function(value) => format!("<fn {}>", value.name),
+            ValueType::Object(object) => {
+                let pointer = Rc::as_ptr(&object.properties) as *const ();
+
+                if ancestors.contains(&pointer) {
+                    return String::from("{...}");
+                }
+
+                let properties = object.properties.borrow();
+
+                if properties.is_empty() {
+                    return String::from("{}");
+                }
+
+                let mut keys: Vec<&String> = properties.keys().collect();
+                keys.sort();
+
+                ancestors.push(pointer);
+
+                let entries: Vec<String> = keys
+                    .into_iter()
+                    .map(|key| {
+                        format!(
+                            "{}: {}",
+                            key,
+                            properties[key].write_display_string(ancestors, true)
+                        )
+                    })
+                    .collect();
+
+                ancestors.pop();
+
+                format!("{{ {} }}", entries.join(", "))
+            }
+            ValueType::Array(array) => {
+                let pointer = Rc::as_ptr(&array.elements) as *const ();
+
+                if ancestors.contains(&pointer) {
+                    return String::from("[...]");
+                }
+
+                ancestors.push(pointer);
+
+                let elements: Vec<String> = array
+                    .elements
+                    .borrow()
+                    .iter()
+                    .map(|element| element.write_display_string(ancestors, true))
+                    .collect();
+
+                ancestors.pop();
+
+                format!("[{}]", elements.join(", "))
+            }
+        }
+    }
+}
+
+fn format_number(value: f64) -> String {
+    match value {
+        value if value.is_infinite() && value > 0.0 => String::from("Infinity"),
+        value if value.is_infinite() => String::from("-Infinity"),
+        value => value.to_string(),
+    }
+}
@@ -1,28 +1,100 @@
+#![allow(clippy::upper_case_acronyms)]
+
+mod diagnostics;
 mod frontend;
 mod helpers;
 mod runtime;
 
+use std::{env, thread};
+
+use crate::diagnostics::{codes::find_error_code, render::Renderer};
 use crate::frontend::parser::lib::Parser;
 use crate::helpers::read_file_contents::read_file_contents_from_args;
 use runtime::environment::Environment;
 use runtime::interpreter::lib::evaluate;
 
+/// Every call to a Cog function recurses on the native stack, so the interpreter
+/// runs on a thread whose stack fits `call_depth::MAX_CALL_DEPTH` nested calls with room to spare.
+const INTERPRETER_STACK_SIZE: usize = 256 * 1024 * 1024;
+
 fn main() {
-    let (file_contents, should_print_ast, should_print_tokens) = read_file_contents_from_args();
-    let mut parser = Parser::new(file_contents);
+    let interpreter = thread::Builder::new()
+        .stack_size(INTERPRETER_STACK_SIZE)
+        .spawn(run)
+        .expect("failed to start the interpreter thread");
+
+    // the panic message has already been printed by the interpreter thread
+    if interpreter.join().is_err() {
+        std::process::exit(101);
+    }
+}
+
+fn run() {
+    let args: Vec<String> = env::args().collect();
+
+    if args.get(1).map(String::as_str) == Some("explain") {
+        explain(args.get(2));
+        return;
+    }
+
+    let args = read_file_contents_from_args();
+    let renderer = Renderer::new(&args.file_path, &args.file_contents, args.use_color);
+
+    let mut parser = match Parser::new(args.file_contents.clone()) {
+        Ok(parser) => parser,
+        Err(error) => {
+            eprint!("{}", renderer.render(&error.to_diagnostic()));
+            std::process::exit(1);
+        }
+    };
     let env = Environment::new(None);
 
-    if should_print_tokens {
+    if args.should_print_tokens {
         println!("{:#?}", parser.tokens);
         return;
     }
 
-    let ast = parser.parse();
+    let result = parser.parse();
+
+    for warning in parser.warnings() {
+        eprint!("{}", renderer.render(warning));
+    }
+
+    let ast = match result {
+        Ok(ast) => ast,
+        Err(errors) => {
+            for error in &errors {
+                eprint!("{}", renderer.render(&error.to_diagnostic()));
+            }
+            std::process::exit(1);
+        }
+    };
 
-    if should_print_ast {
+    if args.should_print_ast {
         println!("{:#?}", ast);
         return;
     }
 
-    evaluate(ast, env);
+    if let Err(error) = evaluate(ast, env) {
+        eprint!("{}", renderer.render(&error.to_diagnostic()));
+        std::process::exit(1);
+    }
+}
+
+fn explain(code: Option<&String>) {
+    let Some(code) = code else {
+        eprintln!("usage: cog explain <code>");
+        std::process::exit(1);
+    };
+
+    match find_error_code(code) {
+        Some(error_code) => println!(
+            "{}: {}\n\n{}",
+            error_code.code, error_code.title, error_code.explanation
+        ),
+        None => {
+            eprintln!("{} is not a known error code", code);
+            std::process::exit(1);
+        }
+    }
 }
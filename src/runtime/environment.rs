@@ -1,7 +1,7 @@
 use std::{
     cell::RefCell,
     collections::{HashMap, HashSet},
-    rc::Rc,
+    rc::{Rc, Weak},
 };
 
 use crate::helpers::{
@@ -11,110 +11,283 @@ use crate::helpers::{
 };
 
 use super::{
+    error::{RuntimeError, RuntimeErrorKind},
     functions::{get_time, print},
-    values::RuntimeValue,
+    values::{Arity, RuntimeValue, ValueType},
 };
 
-pub fn build_scope(env: &mut Environment) {
-    // Global variables
-    env.declare_variable("true".to_string(), build_bool_runtime_value(true), true);
-    env.declare_variable("false".to_string(), build_bool_runtime_value(false), true);
-    env.declare_variable("null".to_string(), build_null_runtime_value(), true);
-
-    // Global functions
-    env.declare_variable("print".to_string(), build_native_function(print), true);
-    env.declare_variable(
-        "get_current_time".to_string(),
-        build_native_function(get_time),
-        true,
-    );
+pub fn build_scope(env: &Environment) {
+    let globals = [
+        // Global variables
+        ("true", build_bool_runtime_value(true)),
+        ("false", build_bool_runtime_value(false)),
+        ("null", build_null_runtime_value()),
+        // Global functions
+        (
+            "print",
+            build_native_function("print", Arity::at_least(0), print),
+        ),
+        (
+            "get_current_time",
+            build_native_function("get_current_time", Arity::exactly(0), get_time),
+        ),
+    ];
+
+    for (name, value) in globals {
+        env.declare_variable(name.to_string(), value, true)
+            .expect("global scope should start empty");
+    }
 }
 
+/// A handle to a lexical scope. Cloning an `Environment` is cheap and shares the
+/// underlying scope, so every closure, block and caller holding a handle observes
+/// the same variables, and assignments are written to the scope that declared them.
+///
+/// Scopes are reference counted, so a cycle between a scope and a value it holds is
+/// never freed. The common cycle, a function stored in the scope it closes over, is
+/// broken by `CapturedScope`. Longer ones are not: a closure stored in an object or
+/// array held by its own scope, or assigned to a variable of an outer scope, keeps
+/// its scope alive until the program ends.
 #[derive(Debug, Clone)]
 pub struct Environment {
-    pub parent: Option<Box<ScopeType>>,
+    scope: Rc<RefCell<Scope>>,
+}
+
+#[derive(Debug)]
+pub struct Scope {
+    pub parent: Option<Environment>,
     pub variables: HashMap<String, RuntimeValue>,
     pub constants: HashSet<String>,
 }
 
+/// The scope a function value closes over.
+///
+/// A function stored in the very scope it captures, as `fn g() {}` and
+/// `let g = fn () {}` are, would keep that scope alive through its own binding. While
+/// it is stored there it only holds the scope weakly, and `peek_variable` hands out
+/// copies holding it strongly again, so the function keeps its scope alive wherever
+/// else it ends up.
 #[derive(Debug, Clone)]
-pub enum ScopeType {
-    Global(Environment),
-    Local(Rc<RefCell<Environment>>),
+pub enum CapturedScope {
+    Strong(Environment),
+    Weak(Weak<RefCell<Scope>>),
+}
+
+impl CapturedScope {
+    pub fn environment(&self) -> Environment {
+        match self {
+            CapturedScope::Strong(environment) => environment.clone(),
+            CapturedScope::Weak(scope) => Environment {
+                scope: scope
+                    .upgrade()
+                    .expect("functions read out of a scope hold it strongly"),
+            },
+        }
+    }
+
+    pub fn ptr_eq(&self, other: &CapturedScope) -> bool {
+        std::ptr::eq(self.as_ptr(), other.as_ptr())
+    }
+
+    fn is_strong_handle_to(&self, env: &Environment) -> bool {
+        matches!(self, CapturedScope::Strong(environment) if environment.ptr_eq(env))
+    }
+
+    fn as_ptr(&self) -> *const RefCell<Scope> {
+        match self {
+            CapturedScope::Strong(environment) => Rc::as_ptr(&environment.scope),
+            CapturedScope::Weak(scope) => scope.as_ptr(),
+        }
+    }
 }
 
 impl Environment {
-    pub fn new(parent: Option<ScopeType>) -> Self {
-        let is_global = match &parent {
-            Some(_) => false,
-            None => true,
-        };
+    pub fn new(parent: Option<Environment>) -> Self {
+        let is_global = parent.is_none();
 
-        let mut env = Environment {
-            parent: match parent {
-                Some(parent) => Some(Box::new(parent)),
-                None => None,
-            },
-            variables: HashMap::new(),
-            constants: HashSet::new(),
+        let env = Environment {
+            scope: Rc::new(RefCell::new(Scope {
+                parent,
+                variables: HashMap::new(),
+                constants: HashSet::new(),
+            })),
         };
 
         if is_global {
-            build_scope(&mut env);
+            build_scope(&env);
         }
 
         env
     }
 
+    /// Creates a new child scope whose parent is this environment.
+    pub fn extend(&self) -> Self {
+        Environment::new(Some(self.clone()))
+    }
+
     pub fn declare_variable(
-        &mut self,
+        &self,
         variable_name: String,
         value: RuntimeValue,
         constant: bool,
-    ) -> RuntimeValue {
-        if self.variables.contains_key(&variable_name) {
-            panic!("Variable {} already declared", variable_name);
+    ) -> Result<RuntimeValue, RuntimeError> {
+        let mut scope = self.scope.borrow_mut();
+
+        if scope.variables.contains_key(&variable_name) {
+            return Err(RuntimeError::new(
+                RuntimeErrorKind::VariableAlreadyDeclared,
+                format!("Variable {} already declared", variable_name),
+            ));
         }
 
-        self.variables.insert(variable_name.clone(), value.clone());
+        scope
+            .variables
+            .insert(variable_name.clone(), self.to_stored(value.clone()));
 
         if constant {
-            self.constants.insert(variable_name.clone());
+            scope.constants.insert(variable_name);
+        }
+
+        Ok(value)
+    }
+
+    pub fn assign_variable(
+        &self,
+        variable_name: String,
+        value: RuntimeValue,
+    ) -> Result<RuntimeValue, RuntimeError> {
+        let environment = self.resolve(variable_name.clone())?;
+        let mut scope = environment.scope.borrow_mut();
+
+        if scope.constants.contains(&variable_name) {
+            return Err(RuntimeError::new(
+                RuntimeErrorKind::ConstantReassignment,
+                format!("Cannot assign to constant variable {}", variable_name),
+            ));
         }
 
-        value
+        scope
+            .variables
+            .insert(variable_name, environment.to_stored(value.clone()));
+
+        Ok(value)
+    }
+
+    pub fn peek_variable(&self, variable_name: String) -> Result<RuntimeValue, RuntimeError> {
+        let environment = self.resolve(variable_name.clone())?;
+        let scope = environment.scope.borrow();
+
+        let value = scope.variables.get(&variable_name).unwrap().clone();
+
+        Ok(environment.to_loaded(value))
     }
 
-    pub fn assign_variable(&mut self, variable_name: String, value: RuntimeValue) -> RuntimeValue {
-        let mut environment = self.resolve(variable_name.clone());
+    /// Makes a function that closes over this scope hold it weakly before it is
+    /// stored here, see `CapturedScope`.
+    fn to_stored(&self, value: RuntimeValue) -> RuntimeValue {
+        match value.value_type {
+            ValueType::Function(mut function) if function.scope.is_strong_handle_to(self) => {
+                function.scope = CapturedScope::Weak(Rc::downgrade(&self.scope));
 
-        if environment.constants.contains(&variable_name) {
-            panic!("Cannot assign to constant variable {}", variable_name);
+                RuntimeValue {
+                    value_type: ValueType::Function(function),
+                }
+            }
+            value_type => RuntimeValue { value_type },
         }
+    }
+
+    /// Gives a function read out of this scope a strong handle to it again.
+    fn to_loaded(&self, value: RuntimeValue) -> RuntimeValue {
+        match value.value_type {
+            ValueType::Function(mut function) => {
+                if let CapturedScope::Weak(_) = function.scope {
+                    function.scope = CapturedScope::Strong(self.clone());
+                }
+
+                RuntimeValue {
+                    value_type: ValueType::Function(function),
+                }
+            }
+            value_type => RuntimeValue { value_type },
+        }
+    }
+
+    /// Walks up the parent chain and returns the environment that declares `variable_name`.
+    pub fn resolve(&self, variable_name: String) -> Result<Environment, RuntimeError> {
+        let scope = self.scope.borrow();
 
-        environment.variables.insert(variable_name, value.clone());
+        if scope.variables.contains_key(&variable_name) {
+            return Ok(self.clone());
+        }
 
-        value
+        match &scope.parent {
+            Some(parent) => parent.resolve(variable_name),
+            None => Err(RuntimeError::new(
+                RuntimeErrorKind::UndefinedVariable,
+                format!("Variable {} not found", variable_name),
+            )),
+        }
     }
 
-    pub fn peek_variable(&self, variable_name: String) -> RuntimeValue {
-        let environment = self.resolve(variable_name.clone());
-        environment.variables.get(&variable_name).unwrap().clone()
+    pub fn ptr_eq(&self, other: &Environment) -> bool {
+        Rc::ptr_eq(&self.scope, &other.scope)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::values::{FunctionValue, ValueTypes};
 
-    pub fn resolve(&self, variable_name: String) -> Environment {
-        if self.variables.contains_key(&variable_name) {
-            return self.clone();
+    fn function_closing_over(env: &Environment) -> RuntimeValue {
+        RuntimeValue {
+            value_type: ValueType::Function(FunctionValue {
+                r#type: ValueTypes::Function,
+                name: String::from("g"),
+                body: Rc::new(Vec::new()),
+                parameters: Vec::new(),
+                scope: CapturedScope::Strong(env.clone()),
+            }),
         }
+    }
 
-        match &self.parent {
-            Some(parent) => match parent.as_ref() {
-                ScopeType::Global(parent) => parent.resolve(variable_name),
-                ScopeType::Local(parent) => parent.borrow().resolve(variable_name),
-            },
-            None => panic!("Variable {} not found", variable_name),
-        };
+    #[test]
+    fn functions_stored_in_their_own_scope_do_not_keep_it_alive() {
+        let block = Environment::new(None).extend();
+        let scope = Rc::downgrade(&block.scope);
+
+        block
+            .declare_variable(String::from("g"), function_closing_over(&block), false)
+            .unwrap();
+        let g = block.peek_variable(String::from("g")).unwrap();
+
+        drop(block);
+        assert!(scope.upgrade().is_some(), "a copy read out keeps the scope");
+
+        drop(g);
+        assert!(
+            scope.upgrade().is_none(),
+            "the scope should have been freed"
+        );
+    }
+
+    #[test]
+    fn functions_assigned_in_their_own_scope_do_not_keep_it_alive() {
+        let block = Environment::new(None).extend();
+        let scope = Rc::downgrade(&block.scope);
+
+        block
+            .declare_variable(String::from("g"), build_null_runtime_value(), false)
+            .unwrap();
+        block
+            .assign_variable(String::from("g"), function_closing_over(&block))
+            .unwrap();
 
-        todo!();
+        drop(block);
+        assert!(
+            scope.upgrade().is_none(),
+            "the scope should have been freed"
+        );
     }
 }
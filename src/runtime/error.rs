@@ -0,0 +1,114 @@
+use std::fmt;
+
+use crate::{
+    diagnostics::{
+        codes::{self, ErrorCode},
+        diagnostic::Diagnostic,
+    },
+    frontend::span::Span,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RuntimeErrorKind {
+    UndefinedVariable,
+    VariableAlreadyDeclared,
+    ConstantReassignment,
+    TypeError,
+    ArityMismatch,
+    DivisionByZero,
+    NotCallable,
+    IndexOutOfBounds,
+    CallDepthExceeded,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeError {
+    pub(crate) kind: RuntimeErrorKind,
+    pub(crate) message: String,
+    pub(crate) span: Option<Span>,
+}
+
+impl RuntimeError {
+    pub fn new(kind: RuntimeErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            span: None,
+        }
+    }
+
+    /// Attaches `span` unless the error already points to a more specific location.
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span.get_or_insert(span);
+        self
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let code = self.kind.error_code();
+        let diagnostic = Diagnostic::error(self.message.clone())
+            .with_code(code)
+            .with_span(self.span)
+            .with_label(code.title);
+
+        match self.kind {
+            RuntimeErrorKind::UndefinedVariable => {
+                diagnostic.with_help("declare the variable with `let` or `const` before using it")
+            }
+            RuntimeErrorKind::VariableAlreadyDeclared => diagnostic
+                .with_note("a name can only be declared once per scope")
+                .with_help("assign to the existing variable instead of declaring it again"),
+            RuntimeErrorKind::ConstantReassignment => {
+                diagnostic.with_help("declare the variable with `let` if it needs to change")
+            }
+            RuntimeErrorKind::CallDepthExceeded => {
+                diagnostic.with_help("check that the recursion reaches a base case")
+            }
+            _ => diagnostic,
+        }
+    }
+}
+
+impl RuntimeErrorKind {
+    pub fn error_code(&self) -> &'static ErrorCode {
+        match self {
+            RuntimeErrorKind::UndefinedVariable => &codes::UNDEFINED_VARIABLE,
+            RuntimeErrorKind::VariableAlreadyDeclared => &codes::VARIABLE_ALREADY_DECLARED,
+            RuntimeErrorKind::ConstantReassignment => &codes::CONSTANT_REASSIGNMENT,
+            RuntimeErrorKind::TypeError => &codes::TYPE_ERROR,
+            RuntimeErrorKind::ArityMismatch => &codes::ARITY_MISMATCH,
+            RuntimeErrorKind::DivisionByZero => &codes::DIVISION_BY_ZERO,
+            RuntimeErrorKind::NotCallable => &codes::NOT_CALLABLE,
+            RuntimeErrorKind::IndexOutOfBounds => &codes::INDEX_OUT_OF_BOUNDS,
+            RuntimeErrorKind::CallDepthExceeded => &codes::CALL_DEPTH_EXCEEDED,
+        }
+    }
+}
+
+impl fmt::Display for RuntimeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RuntimeErrorKind::UndefinedVariable => "undefined variable",
+            RuntimeErrorKind::VariableAlreadyDeclared => "variable already declared",
+            RuntimeErrorKind::ConstantReassignment => "constant reassignment",
+            RuntimeErrorKind::TypeError => "type error",
+            RuntimeErrorKind::ArityMismatch => "arity mismatch",
+            RuntimeErrorKind::DivisionByZero => "division by zero",
+            RuntimeErrorKind::NotCallable => "not callable",
+            RuntimeErrorKind::IndexOutOfBounds => "index out of bounds",
+            RuntimeErrorKind::CallDepthExceeded => "call depth exceeded",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.span {
+            Some(span) => write!(f, "{}: {} at {}", self.kind, self.message, span.start),
+            None => write!(f, "{}: {}", self.kind, self.message),
+        }
+    }
+}
+
+impl std::error::Error for RuntimeError {}
@@ -0,0 +1,42 @@
+use std::cell::Cell;
+
+use crate::runtime::error::{RuntimeError, RuntimeErrorKind};
+
+/// How many calls to user-defined functions can be in progress at once. Every call
+/// recurses on the native stack, so unbounded recursion in a script has to become a
+/// runtime error before it overflows the stack of the interpreter.
+pub const MAX_CALL_DEPTH: usize = 1000;
+
+thread_local! {
+    static CALL_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Counts a function call as in progress until it is dropped, so the depth is
+/// restored however the call ends, including through an error.
+pub struct CallGuard;
+
+impl CallGuard {
+    pub fn enter(function_name: &str) -> Result<Self, RuntimeError> {
+        CALL_DEPTH.with(|depth| {
+            if depth.get() >= MAX_CALL_DEPTH {
+                return Err(RuntimeError::new(
+                    RuntimeErrorKind::CallDepthExceeded,
+                    format!(
+                        "Maximum call depth of {} exceeded when calling {}()",
+                        MAX_CALL_DEPTH, function_name
+                    ),
+                ));
+            }
+
+            depth.set(depth.get() + 1);
+
+            Ok(CallGuard)
+        })
+    }
+}
+
+impl Drop for CallGuard {
+    fn drop(&mut self) {
+        CALL_DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
@@ -0,0 +1,22 @@
+use crate::{
+    helpers::build_null_runtime_value::build_null_runtime_value, runtime::values::RuntimeValue,
+};
+
+/// How a statement finished: normally, producing its value, or through a jump that
+/// skips the rest of every enclosing block until a loop or function call handles it.
+/// `Break` and `Continue` carry the label of the loop they target, if any.
+pub enum ControlFlow {
+    Value(RuntimeValue),
+    Return(RuntimeValue),
+    Break(Option<String>),
+    Continue(Option<String>),
+}
+
+impl ControlFlow {
+    pub fn into_value(self) -> RuntimeValue {
+        match self {
+            ControlFlow::Value(value) | ControlFlow::Return(value) => value,
+            ControlFlow::Break(_) | ControlFlow::Continue(_) => build_null_runtime_value(),
+        }
+    }
+}
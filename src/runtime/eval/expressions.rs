@@ -1,174 +1,739 @@
-use core::panic;
-use std::{cell::RefCell, collections::HashMap, io::Write, rc::Rc};
+use std::{cell::RefCell, cmp::Ordering, collections::HashMap, rc::Rc};
 
 use crate::{
-    frontend::{
-        lexer::lib::{Object, Value},
-        parser::ast::{
-            ASTExpression, ASTExpressionBody, ASTExpressionKind, ASTStatement, ASTStatementKind,
-            BinaryExpression, CallExpression, VariableAssignment,
-        },
+    frontend::parser::ast::{
+        ASTExpression, ASTExpressionKind, BinaryExpression, BinaryOperator, CallExpression,
+        InterpolationPart, LogicalExpression, LogicalOperator, MemberExpression, MemberProperty,
+        Property, UnaryExpression, UnaryOperator, VariableAssignment,
+    },
+    helpers::{
+        build_array_runtime_value::build_array_runtime_value,
+        build_bool_runtime_value::build_bool_runtime_value,
+        build_null_runtime_value::build_null_runtime_value,
+        build_number_runtime_value::build_number_runtime_value,
+        build_string_runtime_value::build_string_runtime_value,
     },
-    helpers::build_null_runtime_value::build_null_runtime_value,
     runtime::{
-        environment::{Environment, ScopeType},
-        values::{NumberValue, ObjectValue, RuntimeValue, ValueType, ValueTypes},
+        environment::Environment,
+        error::{RuntimeError, RuntimeErrorKind},
+        values::{ArrayValue, ObjectValue, RuntimeValue, ValueType, ValueTypes},
     },
 };
 
-use super::statements::{evaluate_expression, evaluate_statement};
+use super::{
+    call_depth::CallGuard,
+    control_flow::ControlFlow,
+    statements::{evaluate_expression, evaluate_statement},
+};
 
-pub fn evaluate_identifier_expression(identifier: String, env: &mut Environment) -> RuntimeValue {
-    let val = env.peek_variable(identifier);
-    val
+pub fn evaluate_identifier_expression(
+    identifier: &str,
+    env: &Environment,
+) -> Result<RuntimeValue, RuntimeError> {
+    env.peek_variable(identifier.to_string())
 }
 
 pub fn evaluate_binary_expression(
-    binary_exp: BinaryExpression,
-    env: &mut Environment,
-) -> RuntimeValue {
-    let left_hand_side = binary_exp.left;
-    let right_hand_side = binary_exp.right;
+    binary_exp: &BinaryExpression,
+    env: &Environment,
+) -> Result<RuntimeValue, RuntimeError> {
+    let operator = binary_exp.operator;
 
-    let left_hand_side = evaluate_expression(*left_hand_side, env);
-    let right_hand_side = evaluate_expression(*right_hand_side, env);
+    let left_hand_side = evaluate_expression(&binary_exp.left, env)?;
+    let right_hand_side = evaluate_expression(&binary_exp.right, env)?;
 
-    if matches!(left_hand_side.value_type, ValueType::Number(_))
-        && matches!(right_hand_side.value_type, ValueType::Number(_))
+    if matches!(
+        operator,
+        BinaryOperator::Equal
+            | BinaryOperator::NotEqual
+            | BinaryOperator::LessThan
+            | BinaryOperator::LessEqual
+            | BinaryOperator::GreaterThan
+            | BinaryOperator::GreaterEqual
+    ) {
+        return Ok(build_bool_runtime_value(evaluate_comparison_expression(
+            left_hand_side,
+            right_hand_side,
+            operator,
+        )?));
+    }
+
+    if let (ValueType::Number(left), ValueType::Number(right)) =
+        (&left_hand_side.value_type, &right_hand_side.value_type)
     {
-        return RuntimeValue {
-            value_type: ValueType::Number(evaluate_numeric_binary_expression(
-                left_hand_side,
-                right_hand_side,
-                binary_exp.operator,
-            )),
-        };
+        return Ok(build_number_runtime_value(
+            evaluate_numeric_binary_expression(left.value, right.value, operator)?,
+        ));
     }
 
-    build_null_runtime_value()
+    Err(RuntimeError::new(
+        RuntimeErrorKind::TypeError,
+        format!(
+            "Cannot apply \"{}\" to {:?} and {:?}",
+            operator,
+            left_hand_side.get_value_type(),
+            right_hand_side.get_value_type()
+        ),
+    ))
 }
 
-pub fn evaluate_numeric_binary_expression(
-    left_hand_side: RuntimeValue,
-    right_hand_side: RuntimeValue,
-    operator: Value,
-) -> NumberValue {
-    let left_hand_side = match left_hand_side.value_type {
-        ValueType::Number(value) => value,
-        _ => panic!("Invalid value type"),
-    };
+/// `&&` and `||` short-circuit: the right operand is only evaluated when the left
+/// one does not decide the result, and the deciding operand itself is returned.
+pub fn evaluate_logical_expression(
+    logical_exp: &LogicalExpression,
+    env: &Environment,
+) -> Result<RuntimeValue, RuntimeError> {
+    let left_hand_side = evaluate_expression(&logical_exp.left, env)?;
 
-    let right_hand_side = match right_hand_side.value_type {
-        ValueType::Number(value) => value,
-        _ => panic!("Invalid value type"),
+    let is_decided = match logical_exp.operator {
+        LogicalOperator::And => !left_hand_side.is_truthy(),
+        LogicalOperator::Or => left_hand_side.is_truthy(),
     };
 
-    let left_hand_side = left_hand_side.value;
-    let right_hand_side = right_hand_side.value;
+    if is_decided {
+        return Ok(left_hand_side);
+    }
+
+    evaluate_expression(&logical_exp.right, env)
+}
+
+/// `!` negates the truthiness of any value, while `-`, `+` and `~` only accept
+/// numbers, `~` (bitwise not) additionally requires an integer.
+pub fn evaluate_unary_expression(
+    unary_exp: &UnaryExpression,
+    env: &Environment,
+) -> Result<RuntimeValue, RuntimeError> {
+    let argument = evaluate_expression(&unary_exp.argument, env)?;
+
+    let operator = unary_exp.operator;
+
+    if operator == UnaryOperator::Not {
+        return Ok(build_bool_runtime_value(!argument.is_truthy()));
+    }
+
+    let value = match argument.value_type {
+        ValueType::Number(number) => number.value,
+        _ => {
+            return Err(RuntimeError::new(
+                RuntimeErrorKind::TypeError,
+                format!(
+                    "Cannot apply unary \"{}\" to {:?}",
+                    operator,
+                    argument.get_value_type()
+                ),
+            ))
+        }
+    };
 
     let result = match operator {
-        Value::String(value) => match value.as_str() {
-            "+" => left_hand_side + right_hand_side,
-            "-" => left_hand_side - right_hand_side,
-            "*" => left_hand_side * right_hand_side,
-            "/" => {
-                if right_hand_side == 0.0 {
-                    panic!("Division by zero");
-                }
+        UnaryOperator::Negate => -value,
+        UnaryOperator::Plus => value,
+        UnaryOperator::BitwiseNot => {
+            // `as i64` saturates, so values outside the i64 range have to be rejected here
+            let fits_in_i64 = value >= i64::MIN as f64 && value < i64::MAX as f64;
 
-                left_hand_side / right_hand_side
+            if value.fract() != 0.0 || !fits_in_i64 {
+                return Err(RuntimeError::new(
+                    RuntimeErrorKind::TypeError,
+                    format!(
+                        "Cannot apply \"~\" to {}, it requires a 64-bit integer",
+                        value
+                    ),
+                ));
             }
-            "%" => left_hand_side % right_hand_side,
-            _ => panic!("Invalid operator"),
-        },
-        _ => panic!("Invalid operator"),
+
+            !(value as i64) as f64
+        }
+        UnaryOperator::Not => unreachable!("\"!\" accepts any value and is handled above"),
     };
 
-    NumberValue {
-        r#type: ValueTypes::Number,
-        value: result,
+    Ok(build_number_runtime_value(result))
+}
+
+/// Equality works across every pair of values (see `RuntimeValue::is_equal`), while
+/// ordering is only defined between two numbers or two strings (compared lexicographically).
+pub fn evaluate_comparison_expression(
+    left_hand_side: RuntimeValue,
+    right_hand_side: RuntimeValue,
+    operator: BinaryOperator,
+) -> Result<bool, RuntimeError> {
+    match operator {
+        BinaryOperator::Equal => return Ok(left_hand_side.is_equal(&right_hand_side)),
+        BinaryOperator::NotEqual => return Ok(!left_hand_side.is_equal(&right_hand_side)),
+        _ => (),
     }
+
+    let ordering = match (&left_hand_side.value_type, &right_hand_side.value_type) {
+        (ValueType::Number(left), ValueType::Number(right)) => left.value.partial_cmp(&right.value),
+        (ValueType::String(left), ValueType::String(right)) => Some(left.value.cmp(&right.value)),
+        _ => {
+            return Err(RuntimeError::new(
+                RuntimeErrorKind::TypeError,
+                format!(
+                    "Cannot compare {:?} with {:?} using \"{}\"",
+                    left_hand_side.get_value_type(),
+                    right_hand_side.get_value_type(),
+                    operator
+                ),
+            ))
+        }
+    };
+
+    // comparisons involving NaN are always false
+    let Some(ordering) = ordering else {
+        return Ok(false);
+    };
+
+    Ok(match operator {
+        BinaryOperator::LessThan => ordering == Ordering::Less,
+        BinaryOperator::LessEqual => ordering != Ordering::Greater,
+        BinaryOperator::GreaterThan => ordering == Ordering::Greater,
+        BinaryOperator::GreaterEqual => ordering != Ordering::Less,
+        operator => unreachable!("\"{}\" is not a comparison operator", operator),
+    })
+}
+
+pub fn evaluate_numeric_binary_expression(
+    left_hand_side: f64,
+    right_hand_side: f64,
+    operator: BinaryOperator,
+) -> Result<f64, RuntimeError> {
+    Ok(match operator {
+        BinaryOperator::Add => left_hand_side + right_hand_side,
+        BinaryOperator::Subtract => left_hand_side - right_hand_side,
+        BinaryOperator::Multiply => left_hand_side * right_hand_side,
+        BinaryOperator::Divide | BinaryOperator::Remainder if right_hand_side == 0.0 => {
+            return Err(RuntimeError::new(
+                RuntimeErrorKind::DivisionByZero,
+                "Division by zero",
+            ))
+        }
+        BinaryOperator::Divide => left_hand_side / right_hand_side,
+        BinaryOperator::Remainder => left_hand_side % right_hand_side,
+        BinaryOperator::Exponent => left_hand_side.powf(right_hand_side),
+        BinaryOperator::Range | BinaryOperator::InclusiveRange => {
+            return Err(RuntimeError::new(
+                RuntimeErrorKind::TypeError,
+                format!("Ranges (\"{}\") can only be used in for loops", operator),
+            ))
+        }
+        operator => unreachable!("\"{}\" is not an arithmetic operator", operator),
+    })
 }
 
 pub fn evaluate_assignment_expression(
-    node: VariableAssignment,
-    env: &mut Environment,
-) -> RuntimeValue {
-    if node.assignee.kind != ASTExpressionKind::Identifier {
-        panic!("Invalid assignee type");
+    node: &VariableAssignment,
+    env: &Environment,
+) -> Result<RuntimeValue, RuntimeError> {
+    match &node.assignee.kind {
+        ASTExpressionKind::Identifier(variable_name) => {
+            let value_to_assign = evaluate_expression(&node.value, env)?;
+            env.assign_variable(variable_name.clone(), value_to_assign)
+        }
+        ASTExpressionKind::MemberExpression(member_expression) => {
+            let object = evaluate_expression(&member_expression.object, env)?;
+
+            let key = match (&member_expression.property, &object.value_type) {
+                (MemberProperty::Computed(index), ValueType::Array(array)) => {
+                    let index = evaluate_array_index(index, array, env)?;
+                    let value_to_assign = evaluate_expression(&node.value, env)?;
+
+                    array.elements.borrow_mut()[index] = value_to_assign.clone();
+
+                    return Ok(value_to_assign);
+                }
+                (property, _) => evaluate_property_key(property, env)?,
+            };
+
+            let object = match object.value_type {
+                ValueType::Object(object) => object,
+                _ => {
+                    return Err(RuntimeError::new(
+                        RuntimeErrorKind::TypeError,
+                        format!(
+                            "Cannot set property \"{}\" on a value of type {:?}",
+                            key,
+                            object.get_value_type()
+                        ),
+                    ))
+                }
+            };
+
+            let value_to_assign = evaluate_expression(&node.value, env)?;
+            object
+                .properties
+                .borrow_mut()
+                .insert(key, value_to_assign.clone());
+
+            Ok(value_to_assign)
+        }
+        _ => unreachable!("the parser only accepts variables and members as assignment targets"),
+    }
+}
+
+/// Builds the string of `"a ${b} c"`, every embedded value is converted with
+/// `RuntimeValue::to_display_string`, the same rule `print` uses.
+pub fn evaluate_interpolated_string(
+    parts: &[InterpolationPart],
+    env: &Environment,
+) -> Result<RuntimeValue, RuntimeError> {
+    let mut result = String::new();
+
+    for part in parts {
+        match part {
+            InterpolationPart::Literal(text) => result.push_str(text),
+            InterpolationPart::Expression(expression) => {
+                result.push_str(&evaluate_expression(expression, env)?.to_display_string())
+            }
+        }
+    }
+
+    Ok(build_string_runtime_value(result))
+}
+
+pub fn evaluate_object_expression(
+    object_properties: &[Property],
+    env: &Environment,
+) -> Result<RuntimeValue, RuntimeError> {
+    let mut properties = HashMap::new();
+
+    for property in object_properties {
+        let key = property.key.clone();
+
+        let runtime_value: RuntimeValue = match &property.value {
+            Some(value) => evaluate_expression(value, env)?,
+            None => env.peek_variable(key.clone())?,
+        };
+
+        properties.insert(key, runtime_value);
     }
 
-    let variable_name = match node.assignee.body {
-        ASTExpressionBody::Value(Value::String(value)) => value,
-        _ => panic!("Invalid value type"),
+    Ok(RuntimeValue {
+        value_type: ValueType::Object(ObjectValue {
+            r#type: ValueTypes::Object,
+            properties: Rc::new(RefCell::new(properties)),
+        }),
+    })
+}
+
+pub fn evaluate_member_expression(
+    member_expression: &MemberExpression,
+    env: &Environment,
+) -> Result<RuntimeValue, RuntimeError> {
+    let object = evaluate_expression(&member_expression.object, env)?;
+
+    let key = match (&member_expression.property, &object.value_type) {
+        (MemberProperty::Computed(index), ValueType::Array(array)) => {
+            let index = evaluate_array_index(index, array, env)?;
+
+            return Ok(array.elements.borrow()[index].clone());
+        }
+        (property, _) => evaluate_property_key(property, env)?,
     };
 
-    let value_to_assign = evaluate_expression(*node.value, env);
-    env.assign_variable(variable_name, value_to_assign)
+    match object.value_type {
+        ValueType::Object(object) => Ok(object
+            .properties
+            .borrow()
+            .get(&key)
+            .cloned()
+            .unwrap_or_else(build_null_runtime_value)),
+        ValueType::Array(array) if key == "length" => Ok(build_number_runtime_value(
+            array.elements.borrow().len() as f64,
+        )),
+        _ => Err(RuntimeError::new(
+            RuntimeErrorKind::TypeError,
+            format!(
+                "Cannot read property \"{}\" of a value of type {:?}",
+                key,
+                object.get_value_type()
+            ),
+        )),
+    }
 }
 
-pub fn evaluate_object_expression(obj: Object, env: &mut Environment) -> RuntimeValue {
-    let mut object = ObjectValue {
-        r#type: ValueTypes::Object,
-        properties: HashMap::new(),
+/// Evaluates the index of `array[index]` and checks it against the array bounds.
+/// Indices must be integers, negative ones count from the end so `-1` is the last element.
+fn evaluate_array_index(
+    property: &ASTExpression,
+    array: &ArrayValue,
+    env: &Environment,
+) -> Result<usize, RuntimeError> {
+    let index = evaluate_expression(property, env)?;
+
+    let index = match index.value_type {
+        ValueType::Number(number) if number.value.fract() == 0.0 => number.value,
+        ValueType::Number(number) => {
+            return Err(RuntimeError::new(
+                RuntimeErrorKind::TypeError,
+                format!("Array indices must be integers, found {}", number.value),
+            ))
+        }
+        _ => {
+            return Err(RuntimeError::new(
+                RuntimeErrorKind::TypeError,
+                format!(
+                    "Array indices must be numbers, found {:?}",
+                    index.get_value_type()
+                ),
+            ))
+        }
     };
 
-    for property in obj.properties {
-        let key = property.key;
-        let value = property.value;
-
-        let runtime_value: RuntimeValue = match value {
-            Some(value) => {
-                let value_statement = ASTStatement {
-                    kind: ASTStatementKind::ExpressionStatement(ASTExpression {
-                        kind: value.kind,
-                        body: value.body,
-                    }),
-                };
+    let length = array.elements.borrow().len();
+    let resolved = match index < 0.0 {
+        true => length as f64 + index,
+        false => index,
+    };
 
-                evaluate_statement(value_statement, env)
-            }
-            None => env.peek_variable(key.clone()),
-        };
+    if resolved < 0.0 || resolved >= length as f64 {
+        return Err(RuntimeError::new(
+            RuntimeErrorKind::IndexOutOfBounds,
+            format!(
+                "Index {} is out of bounds for an array of length {}",
+                index, length
+            ),
+        ));
+    }
+
+    Ok(resolved as usize)
+}
+
+pub fn evaluate_array_expression(
+    elements: &[ASTExpression],
+    env: &Environment,
+) -> Result<RuntimeValue, RuntimeError> {
+    let mut values = Vec::with_capacity(elements.len());
 
-        object.properties.insert(key, runtime_value);
+    for element in elements {
+        values.push(evaluate_expression(element, env)?);
     }
 
-    RuntimeValue {
-        value_type: ValueType::Object(object),
+    Ok(build_array_runtime_value(values))
+}
+
+/// Resolves the key of a member expression: `obj.x` uses the identifier name
+/// as is, while `obj[expr]` evaluates `expr`, which must produce a string or a number.
+pub fn evaluate_property_key(
+    property: &MemberProperty,
+    env: &Environment,
+) -> Result<String, RuntimeError> {
+    let property = match property {
+        MemberProperty::Named(key) => return Ok(key.clone()),
+        MemberProperty::Computed(property) => property,
+    };
+
+    let key = evaluate_expression(property, env)?;
+
+    match key.value_type {
+        ValueType::String(key) => Ok(key.value),
+        ValueType::Number(key) => Ok(key.value.to_string()),
+        _ => Err(RuntimeError::new(
+            RuntimeErrorKind::TypeError,
+            format!(
+                "Property keys must be strings or numbers, found {:?}",
+                key.get_value_type()
+            ),
+        )),
     }
 }
 
 pub fn evaluate_call_expression(
-    call_expression: CallExpression,
-    env: &mut Environment,
-) -> RuntimeValue {
-    let caller = evaluate_expression(*call_expression.caller, env);
+    call_expression: &CallExpression,
+    env: &Environment,
+) -> Result<RuntimeValue, RuntimeError> {
+    let caller = evaluate_expression(&call_expression.caller, env)?;
     let mut arguments = Vec::new();
 
-    for arg in call_expression.arguments {
-        arguments.push(evaluate_expression(arg, env));
+    for arg in &call_expression.arguments {
+        arguments.push(evaluate_expression(arg, env)?);
     }
 
     match caller.value_type {
         ValueType::NativeFunction(native_function) => {
+            native_function
+                .arity
+                .check(&native_function.name, arguments.len())?;
+
             (native_function.call)(arguments, env.clone())
         }
         ValueType::Function(func) => {
-            let mut function_scope = Environment::new(Some(ScopeType::Local(func.scope)));
+            func.arity().check(&func.name, arguments.len())?;
+
+            let _guard = CallGuard::enter(&func.name)?;
+
+            let function_scope = func.scope.environment().extend();
+            let mut arguments = arguments.into_iter();
+
+            for parameter in func.parameters.iter() {
+                // defaults are evaluated in the function scope, so they can refer to
+                // the parameters before them
+                let value = match (arguments.next(), &parameter.default) {
+                    (argument, _) if parameter.rest => build_array_runtime_value(
+                        argument.into_iter().chain(arguments.by_ref()).collect(),
+                    ),
+                    (Some(argument), _) => argument,
+                    (None, Some(default)) => evaluate_expression(default, &function_scope)?,
+                    (None, None) => unreachable!("arity is checked before binding parameters"),
+                };
 
-            for (index, parameter) in func.parameters.iter().enumerate() {
-                function_scope.declare_variable(parameter.clone(), arguments[index].clone(), false);
+                function_scope.declare_variable(parameter.name.clone(), value, false)?;
             }
 
             let mut result: RuntimeValue = build_null_runtime_value();
 
-            for statement in func.body {
-                result = evaluate_statement(statement, &mut function_scope);
+            for statement in func.body.iter() {
+                // break and continue cannot cross a function boundary, the parser rejects them
+                match evaluate_statement(statement, &function_scope)? {
+                    ControlFlow::Value(value) => result = value,
+                    flow => return Ok(flow.into_value()),
+                }
             }
 
-            result
+            Ok(result)
         }
-        _ => panic!("Trying to call a non-function value"),
+        _ => Err(RuntimeError::new(
+            RuntimeErrorKind::NotCallable,
+            format!(
+                "Trying to call a non-function value of type {:?}",
+                caller.get_value_type()
+            ),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime::{
+        error::RuntimeErrorKind,
+        interpreter::lib::{run_source, run_source_error},
+    };
+
+    #[test]
+    fn compares_numbers_and_strings() {
+        assert_eq!(run_source("1 < 2"), "true");
+        assert_eq!(run_source("2 <= 2"), "true");
+        assert_eq!(run_source("3 > 4"), "false");
+        assert_eq!(run_source("\"apple\" < \"banana\""), "true");
+        assert_eq!(run_source("\"b\" >= \"a\""), "true");
+    }
+
+    #[test]
+    fn never_treats_nan_as_equal_or_ordered() {
+        assert_eq!(run_source("let nan = (-1) ** 0.5; nan == nan"), "false");
+        assert_eq!(
+            run_source("let nan = (-1) ** 0.5; nan < 1 || nan >= 1"),
+            "false"
+        );
+    }
+
+    #[test]
+    fn compares_values_of_every_type_for_equality() {
+        assert_eq!(run_source("null == null"), "true");
+        assert_eq!(run_source("true != false"), "true");
+        assert_eq!(run_source("1 == \"1\""), "false");
+        assert_eq!(run_source("{a: 1, b: {c: 2}} == {b: {c: 2}, a: 1}"), "true");
+        assert_eq!(run_source("{a: 1} == {a: 2}"), "false");
+        assert_eq!(run_source("fn f() {} f == f"), "true");
+        assert_eq!(run_source("(() => 1) == (() => 1)"), "false");
+    }
+
+    #[test]
+    fn rejects_ordering_values_of_other_types() {
+        for source in ["1 < \"2\"", "null > null", "{} <= {}"] {
+            assert_eq!(
+                run_source_error(source),
+                RuntimeErrorKind::TypeError,
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn reads_named_and_computed_members() {
+        let source = "let key = \"b\"; let o = {a: {b: 3}};";
+
+        assert_eq!(run_source(&format!("{} o.a.b", source)), "3");
+        assert_eq!(run_source(&format!("{} o[\"a\"][key]", source)), "3");
+        assert_eq!(run_source(&format!("{} o.missing", source)), "null");
+    }
+
+    #[test]
+    fn rejects_reading_members_of_non_objects() {
+        for source in ["let n = 1; n.x", "null.x", "let s = \"s\"; s[\"length\"]"] {
+            assert_eq!(
+                run_source_error(source),
+                RuntimeErrorKind::TypeError,
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn assigns_to_members_through_shared_references() {
+        let source = "let o = {a: {b: 1}};
+            let alias = o;
+            alias.a.b = 5;
+            o[\"c\"] = o.a.b + 1;
+            o";
+
+        assert_eq!(run_source(source), "{ a: { b: 5 }, c: 6 }");
+    }
+
+    #[test]
+    fn compares_objects_that_contain_themselves() {
+        let source = "let a = {}; a.self = a;
+            let b = {}; b.self = b;
+            let c = {x: 1}; c.self = c;
+            a == b && a != c";
+
+        assert_eq!(run_source(source), "true");
+    }
+
+    #[test]
+    fn rejects_assigning_to_members_of_non_objects() {
+        assert_eq!(
+            run_source_error("let n = 1; n.x = 2;"),
+            RuntimeErrorKind::TypeError
+        );
+    }
+
+    #[test]
+    fn reports_runtime_errors_instead_of_panicking() {
+        assert_eq!(
+            run_source_error("missing + 1"),
+            RuntimeErrorKind::UndefinedVariable
+        );
+        assert_eq!(run_source_error("1 / 0"), RuntimeErrorKind::DivisionByZero);
+        assert_eq!(run_source_error("1 % 0"), RuntimeErrorKind::DivisionByZero);
+        assert_eq!(
+            run_source_error("let n = 1; n()"),
+            RuntimeErrorKind::NotCallable
+        );
+    }
+
+    #[test]
+    fn stops_unbounded_recursion() {
+        // every call recurses on the native stack, so run on a stack as large as the interpreter's
+        let kind = std::thread::Builder::new()
+            .stack_size(256 * 1024 * 1024)
+            .spawn(|| run_source_error("fn forever() { return forever(); } forever()"))
+            .expect("failed to start the test thread")
+            .join()
+            .expect("the program should fail without panicking");
+
+        assert_eq!(kind, RuntimeErrorKind::CallDepthExceeded);
+    }
+
+    #[test]
+    fn interpolates_expressions_into_strings() {
+        let source = "let a = 2; let b = 3; let o = {n: [1, true]};
+            \"total: ${a + b}, object: ${o}, nested: ${\"${a}${b}\"}\"";
+
+        assert_eq!(
+            run_source(source),
+            "total: 5, object: { n: [1, true] }, nested: 23"
+        );
+    }
+
+    #[test]
+    fn logical_operators_short_circuit_and_return_the_deciding_operand() {
+        assert_eq!(run_source("false && undefined_name"), "false");
+        assert_eq!(run_source("true || undefined_name"), "true");
+        assert_eq!(run_source("0 || 5"), "5");
+        assert_eq!(run_source("1 && 2"), "2");
+        assert_eq!(run_source("null || \"fallback\""), "fallback");
+    }
+
+    #[test]
+    fn evaluates_unary_operators() {
+        assert_eq!(run_source("let x = 3; -x"), "-3");
+        assert_eq!(run_source("+4"), "4");
+        assert_eq!(run_source("!0"), "true");
+        assert_eq!(run_source("!!\"text\""), "true");
+        assert_eq!(run_source("~5"), "-6");
+    }
+
+    #[test]
+    fn rejects_unary_operators_on_unsupported_operands() {
+        assert_eq!(run_source_error("-\"a\""), RuntimeErrorKind::TypeError);
+        assert_eq!(run_source_error("~1.5"), RuntimeErrorKind::TypeError);
+        assert_eq!(run_source_error("~1e300"), RuntimeErrorKind::TypeError);
+    }
+
+    #[test]
+    fn indexes_arrays_from_either_end() {
+        let source = "let xs = [10, 20, 30];
+            xs[1] = 25;
+            [xs[0], xs[1], xs[-1], xs.length]";
+
+        assert_eq!(run_source(source), "[10, 25, 30, 3]");
+    }
+
+    #[test]
+    fn rejects_indices_outside_the_array() {
+        assert_eq!(
+            run_source_error("let xs = [1]; xs[1]"),
+            RuntimeErrorKind::IndexOutOfBounds
+        );
+        assert_eq!(
+            run_source_error("let xs = [1]; xs[-2] = 0;"),
+            RuntimeErrorKind::IndexOutOfBounds
+        );
+        assert_eq!(
+            run_source_error("let xs = [1]; xs[0.5]"),
+            RuntimeErrorKind::TypeError
+        );
+    }
+
+    #[test]
+    fn closures_keep_their_own_captured_state() {
+        let source = "fn counter() {
+                let count = 0;
+                return () => { count = count + 1; return count; };
+            }
+            let a = counter();
+            let b = counter();
+            a(); a();
+            [a(), b()]";
+
+        assert_eq!(run_source(source), "[3, 1]");
+    }
+
+    #[test]
+    fn calls_anonymous_functions_stored_in_values() {
+        let source = "let ops = {double: (x) => x * 2, square: fn(x) { return x * x; }};
+            [ops.double(4), ops[\"square\"](3), ((x) => x + 1)(1)]";
+
+        assert_eq!(run_source(source), "[8, 9, 2]");
+    }
+
+    #[test]
+    fn fills_default_and_rest_parameters() {
+        let source = "fn f(a, b = a * 10, ...rest) { return [a, b, rest]; }
+            [f(1), f(1, 2), f(1, 2, 3, 4)]";
+
+        assert_eq!(
+            run_source(source),
+            "[[1, 10, []], [1, 2, []], [1, 2, [3, 4]]]"
+        );
+    }
+
+    #[test]
+    fn rejects_calls_with_the_wrong_number_of_arguments() {
+        assert_eq!(
+            run_source_error("fn f(a, b = 1) { return a; } f()"),
+            RuntimeErrorKind::ArityMismatch
+        );
+        assert_eq!(
+            run_source_error("fn f(a, b = 1) { return a; } f(1, 2, 3)"),
+            RuntimeErrorKind::ArityMismatch
+        );
+        assert_eq!(
+            run_source_error("get_current_time(1)"),
+            RuntimeErrorKind::ArityMismatch
+        );
     }
 }
@@ -1,2 +1,4 @@
+pub(crate) mod call_depth;
+pub(crate) mod control_flow;
 pub(crate) mod expressions;
 pub(crate) mod statements;
@@ -1,167 +1,557 @@
-use std::{cell::RefCell, rc::Rc};
+use std::rc::Rc;
 
+use super::control_flow::ControlFlow;
 use super::expressions::{
-    evaluate_assignment_expression, evaluate_binary_expression, evaluate_call_expression,
-    evaluate_identifier_expression, evaluate_object_expression,
+    evaluate_array_expression, evaluate_assignment_expression, evaluate_binary_expression,
+    evaluate_call_expression, evaluate_identifier_expression, evaluate_interpolated_string,
+    evaluate_logical_expression, evaluate_member_expression, evaluate_object_expression,
+    evaluate_unary_expression,
 };
 use crate::{
-    frontend::{
-        lexer::lib::Value,
-        parser::ast::{
-            ASTExpression, ASTExpressionBody, ASTExpressionKind, ASTStatement, ASTStatementKind,
-            FunctionDeclaration, VariableDeclaration, AST,
-        },
+    frontend::parser::ast::{
+        ASTExpression, ASTExpressionKind, ASTStatement, ASTStatementKind, Alternate,
+        BinaryExpression, BinaryOperator, ConditionalStatement, ForStatement, FunctionDeclaration,
+        FunctionExpression, ReturnStatement, VariableDeclaration, WhileStatement, AST,
     },
     helpers::{
         build_null_runtime_value::build_null_runtime_value,
         build_number_runtime_value::build_number_runtime_value,
+        build_string_runtime_value::build_string_runtime_value,
     },
     runtime::{
-        environment::{Environment, ScopeType},
-        values::{FunctionValue, NumberValue, RuntimeValue, StringValue, ValueType, ValueTypes},
+        environment::{CapturedScope, Environment},
+        error::{RuntimeError, RuntimeErrorKind},
+        values::{FunctionValue, RuntimeValue, ValueType, ValueTypes},
     },
 };
 
-pub fn evaluate_program(ast: AST, mut env: Environment) -> RuntimeValue {
+pub fn evaluate_program(ast: AST, env: Environment) -> Result<RuntimeValue, RuntimeError> {
     let mut last_evaluated = build_null_runtime_value();
 
-    for statement in ast.statements {
-        last_evaluated = evaluate_statement(statement, &mut env);
+    for statement in &ast.statements {
+        last_evaluated = evaluate_statement(statement, &env)?.into_value();
     }
 
-    last_evaluated
+    Ok(last_evaluated)
 }
 
-pub fn evaluate_statement(ast_node: ASTStatement, env: &mut Environment) -> RuntimeValue {
-    match ast_node.kind {
-        ASTStatementKind::ExpressionStatement(expression) => evaluate_expression(expression, env),
+pub fn evaluate_statement(
+    ast_node: &ASTStatement,
+    env: &Environment,
+) -> Result<ControlFlow, RuntimeError> {
+    let span = ast_node.span;
+
+    let result = match &ast_node.kind {
+        ASTStatementKind::ExpressionStatement(expression) => {
+            evaluate_expression(expression, env).map(ControlFlow::Value)
+        }
         ASTStatementKind::VariableDeclaration(variable_declaration) => {
-            evaluate_variable_declaration(variable_declaration, env)
+            evaluate_variable_declaration(variable_declaration, env).map(ControlFlow::Value)
         }
         ASTStatementKind::FunctionDeclaration(function_declaration) => {
-            evaluate_function_declaration(function_declaration, env)
-        }
-        _ => panic!("This statement type is not supported yet: {:?}", ast_node),
-    }
-}
-
-pub fn evaluate_expression(expression: ASTExpression, env: &mut Environment) -> RuntimeValue {
-    match expression.kind {
-        ASTExpressionKind::NumericLiteral => RuntimeValue {
-            value_type: ValueType::Number(NumberValue {
-                r#type: ValueTypes::Number,
-                value: match expression.body {
-                    ASTExpressionBody::Value(Value::Number(value)) => value,
-                    _ => panic!("Invalid value type"),
-                },
-            }),
-        },
-        ASTExpressionKind::StringLiteral => RuntimeValue {
-            value_type: ValueType::String(StringValue {
-                r#type: ValueTypes::String,
-                value: match expression.body {
-                    ASTExpressionBody::Value(Value::String(value)) => value,
-                    _ => panic!("Invalid value type"),
-                },
-            }),
-        },
-        ASTExpressionKind::BinaryExpression => {
-            let binary_exp = match expression.body {
-                ASTExpressionBody::BinaryExpressionBody(binary_exp) => binary_exp,
-                _ => panic!("Invalid expression type"),
-            };
+            evaluate_function_declaration(function_declaration, env).map(ControlFlow::Value)
+        }
+        ASTStatementKind::ConditionalStatement(conditional_statement) => {
+            evaluate_conditional_statement(conditional_statement, env)
+        }
+        ASTStatementKind::ForStatement(for_statement) => evaluate_for_statement(for_statement, env),
+        ASTStatementKind::WhileStatement(while_statement) => {
+            evaluate_while_statement(while_statement, env)
+        }
+        ASTStatementKind::ReturnStatement(return_statement) => {
+            evaluate_return_statement(return_statement, env)
+        }
+        ASTStatementKind::BreakStatement(break_statement) => {
+            Ok(ControlFlow::Break(break_statement.label.clone()))
+        }
+        ASTStatementKind::ContinueStatement(continue_statement) => {
+            Ok(ControlFlow::Continue(continue_statement.label.clone()))
+        }
+    };
 
+    result.map_err(|error| error.with_span(span))
+}
+
+pub fn evaluate_expression(
+    expression: &ASTExpression,
+    env: &Environment,
+) -> Result<RuntimeValue, RuntimeError> {
+    let span = expression.span;
+
+    let result = match &expression.kind {
+        ASTExpressionKind::NumericLiteral(value) => Ok(build_number_runtime_value(*value)),
+        ASTExpressionKind::StringLiteral(value) => Ok(build_string_runtime_value(value.clone())),
+        ASTExpressionKind::InterpolatedString(parts) => evaluate_interpolated_string(parts, env),
+        ASTExpressionKind::BinaryExpression(binary_exp) => {
             evaluate_binary_expression(binary_exp, env)
         }
-        ASTExpressionKind::AssignmentExpression => {
-            let assignment_exp = match expression.body {
-                ASTExpressionBody::AssignmentExpressionBody(assignment_exp) => assignment_exp,
-                _ => panic!("Invalid expression type"),
-            };
-
+        ASTExpressionKind::LogicalExpression(logical_exp) => {
+            evaluate_logical_expression(logical_exp, env)
+        }
+        ASTExpressionKind::UnaryExpression(unary_exp) => evaluate_unary_expression(unary_exp, env),
+        ASTExpressionKind::AssignmentExpression(assignment_exp) => {
             evaluate_assignment_expression(assignment_exp, env)
         }
-        ASTExpressionKind::ObjectLiteral => {
-            let object = match expression.body {
-                ASTExpressionBody::Value(Value::Object(object)) => object,
-                _ => panic!("Invalid value type"),
-            };
-
-            evaluate_object_expression(object, env)
+        ASTExpressionKind::ObjectLiteral(properties) => evaluate_object_expression(properties, env),
+        ASTExpressionKind::ArrayLiteral(elements) => evaluate_array_expression(elements, env),
+        ASTExpressionKind::FunctionExpression(function_expression) => {
+            Ok(evaluate_function_expression(function_expression, env))
         }
-        ASTExpressionKind::CallExpression => {
-            let call_expression = match expression.body {
-                ASTExpressionBody::CallExpressionBody(call_expression) => call_expression,
-                _ => panic!("Invalid expression type"),
-            };
-
+        ASTExpressionKind::CallExpression(call_expression) => {
             evaluate_call_expression(call_expression, env)
         }
-        ASTExpressionKind::Identifier => {
-            let identifier = match expression.body {
-                ASTExpressionBody::Value(Value::String(value)) => value,
-                _ => panic!("Invalid value type"),
-            };
-
+        ASTExpressionKind::Identifier(identifier) => {
             evaluate_identifier_expression(identifier, env)
         }
-        _ => panic!(
-            "This expression type is not supported yet: {:?}",
-            expression
-        ),
-    }
+        ASTExpressionKind::MemberExpression(member_expression) => {
+            evaluate_member_expression(member_expression, env)
+        }
+    };
+
+    result.map_err(|error| error.with_span(span))
 }
 
 pub fn evaluate_variable_declaration(
-    variable_declaration_statement: VariableDeclaration,
-    mut env: &mut Environment,
-) -> RuntimeValue {
-    let variable_identifier = match variable_declaration_statement.identifier {
-        Value::String(value) => value,
-        _ => panic!("Invalid value type for variable identifier"),
-    };
-
-    let variable_value = match variable_declaration_statement.value {
-        Some(value) => evaluate_expression(value, &mut env),
+    variable_declaration_statement: &VariableDeclaration,
+    env: &Environment,
+) -> Result<RuntimeValue, RuntimeError> {
+    let variable_value = match &variable_declaration_statement.value {
+        Some(value) => evaluate_expression(value, env)?,
         None => build_null_runtime_value(),
     };
 
     env.declare_variable(
-        variable_identifier,
+        variable_declaration_statement.identifier.clone(),
         variable_value,
         variable_declaration_statement.constant,
     )
 }
 
 pub fn evaluate_function_declaration(
-    function_declaration: FunctionDeclaration,
-    env: &mut Environment,
-) -> RuntimeValue {
-    // Create a new local environment that extends the current environment
-    let local_env = Rc::new(RefCell::new(Environment::new(Some(ScopeType::Local(
-        Rc::new(RefCell::new(env.clone())),
-    )))));
-
-    // Construct the function with the local environment as its scope
+    function_declaration: &FunctionDeclaration,
+    env: &Environment,
+) -> Result<RuntimeValue, RuntimeError> {
+    // The function captures the declaring environment itself, so it observes later
+    // mutations and can call itself recursively through its own name
     let func = RuntimeValue {
         value_type: ValueType::Function(FunctionValue {
             r#type: ValueTypes::Function,
             name: function_declaration.identifier.clone(),
-            parameters: function_declaration.parameters,
-            body: function_declaration.body,
-            scope: Rc::clone(&local_env),
+            parameters: function_declaration.parameters.clone(),
+            body: Rc::clone(&function_declaration.body),
+            scope: CapturedScope::Strong(env.clone()),
+        }),
+    };
+
+    env.declare_variable(function_declaration.identifier.clone(), func.clone(), false)?;
+
+    Ok(func)
+}
+
+pub fn evaluate_return_statement(
+    return_statement: &ReturnStatement,
+    env: &Environment,
+) -> Result<ControlFlow, RuntimeError> {
+    let value = match &return_statement.value {
+        Some(value) => evaluate_expression(value, env)?,
+        None => build_null_runtime_value(),
+    };
+
+    Ok(ControlFlow::Return(value))
+}
+
+/// Anonymous functions capture the scope they are created in, exactly like declared ones.
+pub fn evaluate_function_expression(
+    function_expression: &FunctionExpression,
+    env: &Environment,
+) -> RuntimeValue {
+    RuntimeValue {
+        value_type: ValueType::Function(FunctionValue {
+            r#type: ValueTypes::Function,
+            name: String::from("anonymous"),
+            parameters: function_expression.parameters.clone(),
+            body: Rc::clone(&function_expression.body),
+            scope: CapturedScope::Strong(env.clone()),
         }),
+    }
+}
+
+pub fn evaluate_conditional_statement(
+    conditional_statement: &ConditionalStatement,
+    env: &Environment,
+) -> Result<ControlFlow, RuntimeError> {
+    if evaluate_expression(&conditional_statement.condition, env)?.is_truthy() {
+        return evaluate_block(&conditional_statement.consequence, env);
+    }
+
+    match &conditional_statement.alternate {
+        Some(Alternate::ElseIf(conditional)) => evaluate_conditional_statement(conditional, env),
+        Some(Alternate::Else(block)) => evaluate_block(block, env),
+        None => Ok(ControlFlow::Value(build_null_runtime_value())),
+    }
+}
+
+pub fn evaluate_for_statement(
+    for_statement: &ForStatement,
+    env: &Environment,
+) -> Result<ControlFlow, RuntimeError> {
+    if let ASTExpressionKind::BinaryExpression(BinaryExpression {
+        left,
+        operator: operator @ (BinaryOperator::Range | BinaryOperator::InclusiveRange),
+        right,
+    }) = &for_statement.iterable.kind
+    {
+        let start = evaluate_range_bound(left, env)?;
+        let end = evaluate_range_bound(right, env)?;
+        let inclusive = *operator == BinaryOperator::InclusiveRange;
+
+        // ranges are counted lazily, so `0..100000000` costs nothing up front
+        let mut current = start;
+
+        while current < end || (inclusive && current == end) {
+            let value = build_number_runtime_value(current);
+
+            match evaluate_for_iteration(for_statement, value, env)? {
+                LoopAction::Next => (),
+                LoopAction::Exit => break,
+                LoopAction::Propagate(flow) => return Ok(flow),
+            }
+
+            current += 1.0;
+        }
+
+        return Ok(ControlFlow::Value(build_null_runtime_value()));
+    }
+
+    for value in evaluate_iterable(&for_statement.iterable, env)? {
+        match evaluate_for_iteration(for_statement, value, env)? {
+            LoopAction::Next => (),
+            LoopAction::Exit => break,
+            LoopAction::Propagate(flow) => return Ok(flow),
+        }
+    }
+
+    Ok(ControlFlow::Value(build_null_runtime_value()))
+}
+
+fn evaluate_for_iteration(
+    for_statement: &ForStatement,
+    value: RuntimeValue,
+    env: &Environment,
+) -> Result<LoopAction, RuntimeError> {
+    // the loop variable lives in its own scope around the body, so the body can
+    // shadow it with a `let` of the same name
+    let binding_scope = env.extend();
+    binding_scope.declare_variable(for_statement.identifier.clone(), value, false)?;
+
+    let flow = evaluate_block(&for_statement.body, &binding_scope)?;
+
+    Ok(loop_action(flow, &for_statement.label))
+}
+
+pub fn evaluate_while_statement(
+    while_statement: &WhileStatement,
+    env: &Environment,
+) -> Result<ControlFlow, RuntimeError> {
+    while evaluate_expression(&while_statement.condition, env)?.is_truthy() {
+        let flow = evaluate_block(&while_statement.body, env)?;
+
+        match loop_action(flow, &while_statement.label) {
+            LoopAction::Next => (),
+            LoopAction::Exit => break,
+            LoopAction::Propagate(flow) => return Ok(flow),
+        }
+    }
+
+    Ok(ControlFlow::Value(build_null_runtime_value()))
+}
+
+/// What a loop does after running its body once.
+enum LoopAction {
+    Next,
+    Exit,
+    Propagate(ControlFlow),
+}
+
+/// `break` and `continue` apply to the innermost loop unless they name the label of
+/// an outer one, in which case they are propagated until that loop is reached.
+fn loop_action(flow: ControlFlow, label: &Option<String>) -> LoopAction {
+    let targets_this_loop = |target: &Option<String>| target.is_none() || target == label;
+
+    match flow {
+        ControlFlow::Value(_) => LoopAction::Next,
+        ControlFlow::Continue(target) if targets_this_loop(&target) => LoopAction::Next,
+        ControlFlow::Break(target) if targets_this_loop(&target) => LoopAction::Exit,
+        flow => LoopAction::Propagate(flow),
+    }
+}
+
+/// Produces the values a `for ... in` loop over a collection binds on each iteration:
+/// the elements of an array and the keys of an object, in sorted order. Ranges are
+/// handled by `evaluate_for_statement` itself.
+fn evaluate_iterable(
+    iterable: &ASTExpression,
+    env: &Environment,
+) -> Result<Vec<RuntimeValue>, RuntimeError> {
+    let iterable = evaluate_expression(iterable, env)?;
+
+    match iterable.value_type {
+        ValueType::Object(object) => {
+            let mut keys: Vec<String> = object.properties.borrow().keys().cloned().collect();
+            keys.sort();
+
+            Ok(keys.into_iter().map(build_string_runtime_value).collect())
+        }
+        // iterate over a snapshot, so assigning to elements inside the loop is safe
+        ValueType::Array(array) => Ok(array.elements.borrow().clone()),
+        _ => Err(RuntimeError::new(
+            RuntimeErrorKind::TypeError,
+            format!(
+                "Cannot iterate over a value of type {:?}",
+                iterable.get_value_type()
+            ),
+        )),
+    }
+}
+
+/// The first magnitude at which consecutive integers can no longer all be
+/// represented as `f64`.
+const MAX_SAFE_RANGE_BOUND: f64 = 9_007_199_254_740_992.0;
+
+fn evaluate_range_bound(bound: &ASTExpression, env: &Environment) -> Result<f64, RuntimeError> {
+    let bound = evaluate_expression(bound, env)?;
+
+    match bound.value_type {
+        // past 2^53 adding one no longer changes the number, so the loop would never end
+        ValueType::Number(number) if number.value.abs() >= MAX_SAFE_RANGE_BOUND => {
+            Err(RuntimeError::new(
+                RuntimeErrorKind::TypeError,
+                format!(
+                    "Range bound {} is too large, bounds must be smaller than 2^53 in magnitude",
+                    number.value
+                ),
+            ))
+        }
+        ValueType::Number(number) => Ok(number.value),
+        _ => Err(RuntimeError::new(
+            RuntimeErrorKind::TypeError,
+            format!(
+                "Range bounds must be numbers, found {:?}",
+                bound.get_value_type()
+            ),
+        )),
+    }
+}
+
+/// Runs the statements in a fresh scope, stopping at the first one that does not
+/// complete normally so a `return` skips the rest of the block.
+pub fn evaluate_block(
+    statements: &[ASTStatement],
+    env: &Environment,
+) -> Result<ControlFlow, RuntimeError> {
+    let block_scope = env.extend();
+    let mut last_evaluated = build_null_runtime_value();
+
+    for statement in statements {
+        match evaluate_statement(statement, &block_scope)? {
+            ControlFlow::Value(value) => last_evaluated = value,
+            flow => return Ok(flow),
+        }
+    }
+
+    Ok(ControlFlow::Value(last_evaluated))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime::{
+        error::RuntimeErrorKind,
+        interpreter::lib::{run_source, run_source_error},
     };
 
-    // Declare the function in the new local environment
-    local_env.borrow_mut().declare_variable(
-        function_declaration.identifier.clone(),
-        func.clone(),
-        false,
-    );
+    #[test]
+    fn runs_the_first_branch_whose_condition_is_truthy() {
+        let source = "let x = 2;
+            if x == 1 { \"one\" } else if x == 2 { \"two\" } else { \"many\" }";
+
+        assert_eq!(run_source(source), "two");
+        assert_eq!(
+            run_source("if false { 1 } else if null { 2 } else { 3 }"),
+            "3"
+        );
+        assert_eq!(run_source("if false { 1 }"), "null");
+    }
+
+    #[test]
+    fn applies_the_truthiness_rule_to_every_type() {
+        let truthy = ["true", "1", "-0.5", "\"a\"", "{}", "[]", "print", "() => 0"];
+        let falsy = ["false", "null", "0", "\"\"", "(-1) ** 0.5"];
+
+        for condition in truthy {
+            let source = format!("let c = {};\nif c {{ 1 }} else {{ 2 }}", condition);
+
+            assert_eq!(run_source(&source), "1", "{}", condition);
+        }
+
+        for condition in falsy {
+            let source = format!("let c = {};\nif c {{ 1 }} else {{ 2 }}", condition);
+
+            assert_eq!(run_source(&source), "2", "{}", condition);
+        }
+    }
+
+    #[test]
+    fn gives_each_branch_its_own_scope() {
+        assert_eq!(run_source("let x = 1; if true { let x = 2; } x"), "1");
+        assert_eq!(
+            run_source_error("if true { let y = 2; } y"),
+            RuntimeErrorKind::UndefinedVariable
+        );
+    }
+
+    #[test]
+    fn counts_through_exclusive_and_inclusive_ranges() {
+        assert_eq!(
+            run_source("let total = 0; for i in 1..4 { total = total + i; } total"),
+            "6"
+        );
+        assert_eq!(
+            run_source("let total = 0; for i in 1..=4 { total = total + i; } total"),
+            "10"
+        );
+        assert_eq!(
+            run_source("let runs = 0; for i in 3..1 { runs = runs + 1; } runs"),
+            "0"
+        );
+    }
+
+    #[test]
+    fn runs_while_loops_until_the_condition_is_falsy() {
+        assert_eq!(run_source("let n = 0; while n < 5 { n = n + 2; } n"), "6");
+    }
+
+    #[test]
+    fn iterates_over_object_keys_in_sorted_order() {
+        let source = "let keys = \"\";
+            for key in {b: 1, c: 2, a: 3} { keys = \"${keys}${key}\"; }
+            keys";
+
+        assert_eq!(run_source(source), "abc");
+    }
+
+    #[test]
+    fn lets_loop_bodies_shadow_the_loop_variable() {
+        assert_eq!(
+            run_source("let last = 0; for i in 0..3 { let i = i * 10; last = i; } last"),
+            "20"
+        );
+    }
+
+    #[test]
+    fn rejects_ranges_that_cannot_be_counted() {
+        for source in [
+            "for i in 9007199254740992..9007199254740994 {}",
+            "for i in 0..\"3\" {}",
+            "for i in 5 {}",
+        ] {
+            assert_eq!(
+                run_source_error(source),
+                RuntimeErrorKind::TypeError,
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn closures_observe_later_assignments() {
+        let source = "let x = 1;
+            fn get() { return x; }
+            x = 2;
+            get()";
+
+        assert_eq!(run_source(source), "2");
+    }
 
-    // Also declare the function in the original environment so it's accessible outside
-    env.declare_variable(function_declaration.identifier, func.clone(), false);
+    #[test]
+    fn functions_call_themselves_by_name() {
+        let source = "fn fact(n) { if n <= 1 { return 1; } return n * fact(n - 1); }
+            fact(5)";
 
-    func
+        assert_eq!(run_source(source), "120");
+    }
+
+    #[test]
+    fn reports_misuse_of_bindings() {
+        assert_eq!(
+            run_source_error("let a = 1; let a = 2;"),
+            RuntimeErrorKind::VariableAlreadyDeclared
+        );
+        assert_eq!(
+            run_source_error("const a = 1; a = 2;"),
+            RuntimeErrorKind::ConstantReassignment
+        );
+        assert_eq!(
+            run_source_error("missing = 1;"),
+            RuntimeErrorKind::UndefinedVariable
+        );
+    }
+
+    #[test]
+    fn returns_early_from_inside_loops() {
+        let source = "fn first_even(limit) {
+                for i in 1..limit {
+                    if i % 2 == 0 { return i; }
+                }
+                return -1;
+            }
+            [first_even(10), first_even(2)]";
+
+        assert_eq!(run_source(source), "[2, -1]");
+    }
+
+    #[test]
+    fn bare_returns_produce_null_and_falling_off_the_end_produces_the_last_value() {
+        assert_eq!(run_source("fn f() { return; 1 } f()"), "null");
+        assert_eq!(run_source("fn g() { let x = 1; x + 1 } g()"), "2");
+    }
+
+    #[test]
+    fn break_and_continue_affect_the_innermost_loop() {
+        let source = "let sum = 0;
+            let i = 0;
+            while i < 10 {
+                i = i + 1;
+                if i % 2 == 0 { continue; }
+                if i > 5 { break; }
+                sum = sum + i;
+            }
+            [sum, i]";
+
+        assert_eq!(run_source(source), "[9, 7]");
+    }
+
+    #[test]
+    fn labeled_break_and_continue_affect_the_named_loop() {
+        let source = "let pairs = 0;
+            'outer: for i in 0..3 {
+                for j in 0..3 {
+                    if j == 1 { continue 'outer; }
+                    if i == 2 { break 'outer; }
+                    pairs = pairs + 1;
+                }
+            }
+            pairs";
+
+        assert_eq!(run_source(source), "2");
+    }
+
+    #[test]
+    fn iterates_over_array_elements() {
+        let source = "let total = 0;
+            for x in [1, 2, 3] { total = total + x; }
+            total";
+
+        assert_eq!(run_source(source), "6");
+    }
 }
@@ -2,55 +2,31 @@ use crate::helpers::build_null_runtime_value::build_null_runtime_value;
 
 use super::{
     environment::Environment,
+    error::RuntimeError,
     values::{NumberValue, RuntimeValue, ValueType, ValueTypes},
 };
 
-pub fn print(args: Vec<RuntimeValue>, _scope: Environment) -> RuntimeValue {
+pub fn print(args: Vec<RuntimeValue>, _scope: Environment) -> Result<RuntimeValue, RuntimeError> {
     for arg in args {
-        let arg_value_type = arg.value_type;
-
-        match arg_value_type {
-            ValueType::Number(value) => {
-                print!("{}", value.value);
-            }
-            ValueType::Boolean(value) => {
-                print!("{}", value.value);
-            }
-            ValueType::String(value) => {
-                print!("{}", value.value);
-            }
-            ValueType::Null(value) => {
-                print!("{}", value.value);
-            }
-            ValueType::Object(value) => {
-                print!("{:#?}", value);
-            }
-            ValueType::NativeFunction(value) => {
-                print!("{:#?}", value);
-            }
-            ValueType::Function(value) => {
-                print!("{:#?}", value);
-            }
-        }
+        print!("{}", arg.to_display_string());
     }
 
-    build_null_runtime_value()
+    Ok(build_null_runtime_value())
 }
 
-pub fn get_time(args: Vec<RuntimeValue>, _scope: Environment) -> RuntimeValue {
-    if args.len() > 0 {
-        panic!("get_time() takes no arguments");
-    }
-
+pub fn get_time(
+    _args: Vec<RuntimeValue>,
+    _scope: Environment,
+) -> Result<RuntimeValue, RuntimeError> {
     let time = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap()
         .as_millis();
 
-    RuntimeValue {
+    Ok(RuntimeValue {
         value_type: ValueType::Number(NumberValue {
             r#type: ValueTypes::Number,
             value: time as f64,
         }),
-    }
+    })
 }
@@ -1,11 +1,30 @@
 use crate::{
     frontend::parser::ast::AST,
-    runtime::{environment::Environment, eval::statements::evaluate_program, values::RuntimeValue},
+    runtime::{
+        environment::Environment, error::RuntimeError, eval::statements::evaluate_program,
+        values::RuntimeValue,
+    },
 };
+#[cfg(test)]
+use crate::{frontend::parser::lib::parse_source, runtime::error::RuntimeErrorKind};
 
-pub fn evaluate(ast: AST, env: Environment) -> RuntimeValue {
-    match ast.kind {
-        "Program" => evaluate_program(ast, env),
-        _ => panic!("Unknown AST kind: {}", ast.kind),
-    }
+pub fn evaluate(ast: AST, env: Environment) -> Result<RuntimeValue, RuntimeError> {
+    evaluate_program(ast, env)
+}
+
+/// Runs `source` in a fresh global scope for a test and shows the value of its last
+/// statement the way `print` would, failing the test on any error.
+#[cfg(test)]
+pub(crate) fn run_source(source: &str) -> String {
+    evaluate(parse_source(source), Environment::new(None))
+        .expect("program should run")
+        .to_display_string()
+}
+
+/// Runs `source` for a test that expects it to fail at runtime and returns why.
+#[cfg(test)]
+pub(crate) fn run_source_error(source: &str) -> RuntimeErrorKind {
+    evaluate(parse_source(source), Environment::new(None))
+        .expect_err("program should fail")
+        .kind
 }
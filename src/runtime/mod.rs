@@ -1,4 +1,5 @@
 pub(crate) mod environment;
+pub(crate) mod error;
 pub(crate) mod eval;
 pub(crate) mod functions;
 pub(crate) mod interpreter;
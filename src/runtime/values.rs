@@ -1,8 +1,11 @@
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
-use crate::frontend::parser::ast::ASTStatement;
+use crate::frontend::parser::ast::{ASTStatement, Parameter};
 
-use super::environment::Environment;
+use super::{
+    environment::{CapturedScope, Environment},
+    error::RuntimeError,
+};
 
 #[derive(Debug, Clone)]
 pub(crate) enum ValueType {
@@ -10,6 +13,7 @@ pub(crate) enum ValueType {
     Number(NumberValue),
     Boolean(BooleanValue),
     Object(ObjectValue),
+    Array(ArrayValue),
     String(StringValue),
     NativeFunction(NativeFunctionValue),
     Function(FunctionValue),
@@ -21,6 +25,7 @@ pub enum ValueTypes {
     Number,
     Boolean,
     Object,
+    Array,
     Function,
     String,
 }
@@ -36,20 +41,30 @@ pub struct NullValue {
     pub(crate) value: String,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+/// How many arguments a function accepts, `max` is `None` when there is no upper bound.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arity {
+    pub(crate) min: usize,
+    pub(crate) max: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
 pub struct NativeFunctionValue {
     pub(crate) r#type: ValueTypes,
-    pub(crate) call: fn(Vec<RuntimeValue>, env: Environment) -> RuntimeValue,
+    pub(crate) name: String,
+    pub(crate) arity: Arity,
+    pub(crate) call: fn(Vec<RuntimeValue>, env: Environment) -> Result<RuntimeValue, RuntimeError>,
 }
 
 //TODO: find a way to make this work without breaking everything
+#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct FunctionValue {
     pub(crate) r#type: ValueTypes,
     pub(crate) name: String,
-    pub(crate) body: Vec<ASTStatement>,
-    pub(crate) parameters: Vec<String>,
-    pub(crate) scope: Rc<RefCell<Environment>>,
+    pub(crate) body: Rc<Vec<ASTStatement>>,
+    pub(crate) parameters: Vec<Parameter>,
+    pub(crate) scope: CapturedScope,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -70,8 +85,17 @@ pub struct StringValue {
     pub(crate) value: String,
 }
 
+/// Objects have reference semantics: cloning an `ObjectValue` shares the same
+/// properties, so mutations through one handle are visible through every other.
 #[derive(Debug, Clone)]
 pub struct ObjectValue {
     pub(crate) r#type: ValueTypes,
-    pub(crate) properties: HashMap<String, RuntimeValue>,
+    pub(crate) properties: Rc<RefCell<HashMap<String, RuntimeValue>>>,
+}
+
+/// Arrays share their elements between copies, like objects do.
+#[derive(Debug, Clone)]
+pub struct ArrayValue {
+    pub(crate) r#type: ValueTypes,
+    pub(crate) elements: Rc<RefCell<Vec<RuntimeValue>>>,
 }